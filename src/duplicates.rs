@@ -0,0 +1,110 @@
+//! Grouping of matched files by identical contents, for `--duplicates`
+//!
+//! Candidates are narrowed in rounds, each cheaper than the next: files are
+//! grouped by size, then by a hash of their first few KB, and only files that
+//! still collide are hashed in full.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use slog::{warn, Logger};
+
+use crate::predicate::{hash_reader, Digest, HashAlgorithm};
+
+/// How much of each file the partial hash covers
+const PARTIAL_HASH_SIZE: u64 = 4096;
+
+/// Group `paths` into sets of files with identical contents
+///
+/// Only regular files are considered, and files that can't be read are
+/// skipped. Groups and the files within them keep the order of `paths`.
+pub fn find_duplicates(logger: &Logger, paths: &[PathBuf]) -> Vec<Vec<PathBuf>> {
+    let sized = paths
+        .iter()
+        .filter_map(|path| match std::fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_file() => Some((metadata.len(), path.clone())),
+            Ok(_) => None,
+            Err(e) => {
+                warn!(logger, "skipping file for duplicate check"; "path" => #?path, "error" => %e);
+                None
+            }
+        });
+
+    let mut groups = Vec::new();
+    for (size, same_size) in group_by(sized) {
+        // Files that fit in the partial hash are hashed in full by it
+        let full_hash_needed = size > PARTIAL_HASH_SIZE;
+
+        let partial = same_size
+            .into_iter()
+            .filter_map(|path| Some((hash(logger, &path, Some(PARTIAL_HASH_SIZE))?, path)));
+        for (_, same_prefix) in group_by(partial) {
+            if !full_hash_needed {
+                groups.push(same_prefix);
+                continue;
+            }
+
+            let full = same_prefix
+                .into_iter()
+                .filter_map(|path| Some((hash(logger, &path, None)?, path)));
+            groups.extend(group_by(full).into_iter().map(|(_, g)| g));
+        }
+    }
+
+    // Rounds regroup files, so restore the order of the input
+    let position: HashMap<&Path, usize> = paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| (path.as_path(), i))
+        .collect();
+    for group in &mut groups {
+        group.sort_by_key(|path| position[path.as_path()]);
+    }
+    groups.sort_by_key(|group| position[group[0].as_path()]);
+
+    groups
+}
+
+/// Group items by key, dropping groups of one, in order of first appearance
+fn group_by<K: Eq + Hash + Copy>(
+    items: impl Iterator<Item = (K, PathBuf)>,
+) -> Vec<(K, Vec<PathBuf>)> {
+    let mut order = Vec::new();
+    let mut groups: HashMap<K, Vec<PathBuf>> = HashMap::new();
+    for (k, path) in items {
+        groups
+            .entry(k)
+            .or_insert_with(|| {
+                order.push(k);
+                Vec::new()
+            })
+            .push(path);
+    }
+
+    order
+        .into_iter()
+        .filter_map(|k| {
+            let group = groups.remove(&k)?;
+            (group.len() > 1).then_some((k, group))
+        })
+        .collect()
+}
+
+/// Blake3 digest of a file, or of its first `limit` bytes
+fn hash(logger: &Logger, path: &Path, limit: Option<u64>) -> Option<Digest> {
+    let result = File::open(path).and_then(|file| match limit {
+        Some(limit) => hash_reader(HashAlgorithm::Blake3, file.take(limit)),
+        None => hash_reader(HashAlgorithm::Blake3, file),
+    });
+
+    match result {
+        Ok(digest) => Some(digest),
+        Err(e) => {
+            warn!(logger, "skipping file for duplicate check"; "path" => #?path, "error" => %e);
+            None
+        }
+    }
+}
@@ -1,72 +1,295 @@
 use crate::expr::short_circuit::ShortCircuit;
 use crate::expr::Expr;
-use crate::predicate::{Predicate, StreamingCompiledContentPredicateRef};
+use crate::predicate::{
+    ContentDecoder, ContentPredicateRef, Digest, HashAlgorithm, HashPredicate, Hasher, LineStats,
+    LinesPredicate, MetadataPredicate, NamePredicate, NumberMatcher, PatternScope, Predicate,
+    TextEncoding,
+};
 use crate::util::Done;
+use count::MatchCounter;
 use futures::{Stream, StreamExt};
-use regex_automata::dfa::Automaton;
+use line_match::{LineMatcher, NearMatcher};
+use regex_automata::dfa::{dense::DFA, Automaton};
+use regex_automata::util::primitives::StateID;
+use std::sync::Arc;
 use tokio::io::{self};
 
+mod count;
 pub mod fs;
+mod line_match;
 pub mod structured;
+pub mod tree;
+
+/// Borrow a typechecked expression for evaluation, sharing its compiled DFAs
+pub fn borrow_expr(
+    e: &Expr<Predicate>,
+) -> Expr<Predicate<NamePredicate, MetadataPredicate, ContentPredicateRef<'_>>> {
+    e.map_predicate_ref(|p| match p {
+        Predicate::Name(n) => Predicate::Name(Arc::clone(n)),
+        Predicate::Metadata(m) => Predicate::Metadata(Arc::clone(m)),
+        Predicate::Content(c) => Predicate::Content(c.as_ref()),
+        Predicate::Structured(s) => Predicate::Structured(s.clone()),
+    })
+}
+
+/// Per-file state of a content predicate while its file streams past
+#[derive(Clone)]
+enum ContentState<'a> {
+    /// Fed the decoded stream at the given index
+    Regex(&'a DFA<Vec<u32>>, StateID, usize),
+    /// Fed the decoded stream at the given index, one line at a time, for
+    /// line patterns and windows from the start of the file
+    Line(LineMatcher<'a>, usize),
+    /// Fed the decoded stream at the given index, tracking the lines each
+    /// pattern matches
+    Near(NearMatcher<'a>, usize),
+    /// Resolved at end of input from the shared hasher for its algorithm
+    Hash(&'a HashPredicate),
+    /// Resolved from the line stats of the decoded stream at the given index
+    Lines(&'a LinesPredicate, usize),
+    /// Fed the decoded stream at the given index, counting matches
+    Count(Box<MatchCounter<'a>>, &'a NumberMatcher, usize),
+}
+
+/// The contents decoded from one encoding, shared by the predicates reading it
+struct DecodedStream {
+    encoding: Option<TextEncoding>,
+    decoder: ContentDecoder,
+    /// Only gathered when a line predicate reads this stream
+    lines: Option<LineStats>,
+}
+
+/// Index of the stream decoding from `encoding`, added if needed
+fn decoded_stream(streams: &mut Vec<DecodedStream>, encoding: Option<TextEncoding>) -> usize {
+    match streams.iter().position(|s| s.encoding == encoding) {
+        Some(idx) => idx,
+        None => {
+            streams.push(DecodedStream {
+                encoding,
+                decoder: ContentDecoder::new(encoding),
+                lines: None,
+            });
+            streams.len() - 1
+        }
+    }
+}
 
 pub async fn run_contents_predicate_stream(
-    e: Expr<Predicate<Done, Done, StreamingCompiledContentPredicateRef<'_>>>,
+    e: Expr<Predicate<Done, Done, ContentPredicateRef<'_>>>,
     mut s: impl Stream<Item = io::Result<Vec<u8>>> + std::marker::Unpin,
 ) -> io::Result<Expr<Predicate<Done, Done, Done>>> {
     let config = regex_automata::util::start::Config::new();
 
-    // Initialize state for DFA patterns
-    let mut e: Expr<Predicate<Done, Done, _>> = e.map_predicate(|p| match p {
-        Predicate::Content(pred) => {
+    // Initialize state for DFA patterns, one decoded stream per content
+    // encoding, and one hasher per digest algorithm in use. Hashers see the
+    // raw bytes.
+    let mut streams: Vec<DecodedStream> = Vec::new();
+    let mut hashers: Vec<Hasher> = Vec::new();
+    let mut e: Expr<Predicate<Done, Done, _>> = e.reduce_predicate_and_short_circuit(|p| match p {
+        Predicate::Content(ContentPredicateRef::Regex(pred))
+            if pred.scope != PatternScope::File =>
+        {
+            let stream = decoded_stream(&mut streams, pred.encoding);
+            let matcher = LineMatcher::new(pred.inner, pred.scope == PatternScope::AllLines);
+            ShortCircuit::Unknown(Predicate::Content(ContentState::Line(matcher, stream)))
+        }
+        Predicate::Content(ContentPredicateRef::Regex(pred)) => {
             let dfa = pred.inner;
             let s = dfa
                 .start_state(&config)
                 .expect("DFA start_state failed: invalid regex configuration");
-            Predicate::Content((dfa, s))
+            let stream = decoded_stream(&mut streams, pred.encoding);
+            ShortCircuit::Unknown(Predicate::Content(ContentState::Regex(dfa, s, stream)))
+        }
+        Predicate::Content(ContentPredicateRef::Window(pred, window)) => {
+            let window = window
+                .from_start()
+                .expect("tail windows are resolved before streaming");
+            let stream = decoded_stream(&mut streams, pred.encoding);
+            let matcher = LineMatcher::new(pred.inner, false).with_window(window);
+            ShortCircuit::Unknown(Predicate::Content(ContentState::Line(matcher, stream)))
+        }
+        Predicate::Content(ContentPredicateRef::Near(first, second, distance)) => {
+            let stream = decoded_stream(&mut streams, first.encoding);
+            let matcher = NearMatcher::new(first.inner, second.inner, distance);
+            ShortCircuit::Unknown(Predicate::Content(ContentState::Near(matcher, stream)))
+        }
+        Predicate::Content(ContentPredicateRef::Lines(pred)) => {
+            let stream = decoded_stream(&mut streams, pred.encoding);
+            streams[stream].lines.get_or_insert_with(LineStats::new);
+            ShortCircuit::Unknown(Predicate::Content(ContentState::Lines(pred, stream)))
+        }
+        Predicate::Content(ContentPredicateRef::Count(pred, matcher)) => {
+            let stream = decoded_stream(&mut streams, pred.encoding);
+            let counter = Box::new(MatchCounter::new(pred.inner));
+            ShortCircuit::Unknown(Predicate::Content(ContentState::Count(
+                counter, matcher, stream,
+            )))
+        }
+        Predicate::Content(ContentPredicateRef::Hash(pred)) => {
+            if !hashers.iter().any(|h| h.algorithm() == pred.algorithm) {
+                hashers.push(Hasher::new(pred.algorithm));
+            }
+            ShortCircuit::Unknown(Predicate::Content(ContentState::Hash(pred)))
+        }
+        Predicate::Content(ContentPredicateRef::Header(_)) => {
+            unreachable!("header predicates are resolved before streaming")
         }
         _ => unreachable!(),
     });
 
     while let Some(next) = s.next().await {
+        // Nothing left that depends on the rest of the file
+        if let Expr::Literal(_) = e {
+            break;
+        }
+
         // read the next buffered chunk of bytes
         let bytes = next?;
 
+        for hasher in &mut hashers {
+            hasher.update(&bytes);
+        }
+        let decoded: Vec<_> = streams
+            .iter_mut()
+            .map(|stream| {
+                let decoded = stream.decoder.decode(&bytes, false);
+                if let Some(lines) = &mut stream.lines {
+                    lines.update(&decoded);
+                }
+                decoded
+            })
+            .collect();
+
         // advance each pattern appropriately
-        e = e.reduce_predicate_and_short_circuit(move |p| match p {
-            Predicate::Content((dfa, state)) => {
-                // DFA streaming processing
-                let mut next_state = state;
-                let mut iter = bytes.iter();
-
-                loop {
-                    if let Some(byte) = iter.next() {
-                        next_state = dfa.next_state(next_state, *byte);
-
-                        if dfa.is_match_state(next_state) {
-                            break ShortCircuit::Known(true);
-                        }
-
-                        if dfa.is_dead_state(next_state) {
-                            break ShortCircuit::Known(false);
-                        }
-                    } else {
-                        break ShortCircuit::Unknown(Predicate::Content((dfa, next_state)));
+        e = e.reduce_predicate_and_short_circuit(|p| match p {
+            Predicate::Content(ContentState::Regex(dfa, state, stream)) => {
+                match advance_dfa(dfa, state, &decoded[stream]) {
+                    Ok(next_state) => ShortCircuit::Unknown(Predicate::Content(
+                        ContentState::Regex(dfa, next_state, stream),
+                    )),
+                    Err(matched) => ShortCircuit::Known(matched),
+                }
+            }
+            Predicate::Content(ContentState::Line(mut matcher, stream)) => {
+                matcher.feed(&decoded[stream]);
+                match matcher.result() {
+                    Some(matched) => ShortCircuit::Known(matched),
+                    None => ShortCircuit::Unknown(Predicate::Content(ContentState::Line(
+                        matcher, stream,
+                    ))),
+                }
+            }
+            Predicate::Content(ContentState::Near(mut matcher, stream)) => {
+                matcher.feed(&decoded[stream]);
+                match matcher.result() {
+                    Some(matched) => ShortCircuit::Known(matched),
+                    None => ShortCircuit::Unknown(Predicate::Content(ContentState::Near(
+                        matcher, stream,
+                    ))),
+                }
+            }
+            Predicate::Content(hash @ ContentState::Hash(_)) => {
+                ShortCircuit::Unknown(Predicate::Content(hash))
+            }
+            Predicate::Content(ContentState::Lines(pred, stream)) => {
+                let lines = streams[stream]
+                    .lines
+                    .as_ref()
+                    .expect("line stats are gathered");
+                match pred.eval_partial(lines) {
+                    Some(matched) => ShortCircuit::Known(matched),
+                    None => {
+                        ShortCircuit::Unknown(Predicate::Content(ContentState::Lines(pred, stream)))
                     }
                 }
             }
+            Predicate::Content(ContentState::Count(mut counter, matcher, stream)) => {
+                counter.feed(&decoded[stream]);
+                let decided = if counter.is_done() {
+                    Some(matcher.is_match(counter.count()))
+                } else {
+                    matcher.decide_at_least(counter.count())
+                };
+                match decided {
+                    Some(matched) => ShortCircuit::Known(matched),
+                    None => ShortCircuit::Unknown(Predicate::Content(ContentState::Count(
+                        counter, matcher, stream,
+                    ))),
+                }
+            }
             _ => unreachable!(),
         });
     }
 
-    // Final evaluation
+    // Final evaluation, after flushing any character cut off at the end
+    let flushed: Vec<Vec<u8>> = streams
+        .iter_mut()
+        .map(|stream| {
+            let flushed = stream.decoder.decode(&[], true).into_owned();
+            if let Some(lines) = &mut stream.lines {
+                lines.update(&flushed);
+                lines.finish();
+            }
+            flushed
+        })
+        .collect();
+    let digests: Vec<(HashAlgorithm, Digest)> = hashers
+        .into_iter()
+        .map(|h| (h.algorithm(), h.finalize()))
+        .collect();
     let e = e.reduce_predicate_and_short_circuit(|p| match p {
-        Predicate::Content((dfa, state)) => {
-            let next_state = dfa.next_eoi_state(state);
-            let matched = dfa.is_match_state(next_state);
-            ShortCircuit::Known(matched)
+        Predicate::Content(ContentState::Regex(dfa, state, stream)) => {
+            match advance_dfa(dfa, state, &flushed[stream]) {
+                Ok(state) => ShortCircuit::Known(dfa.is_match_state(dfa.next_eoi_state(state))),
+                Err(matched) => ShortCircuit::Known(matched),
+            }
+        }
+        Predicate::Content(ContentState::Line(mut matcher, stream)) => {
+            matcher.feed(&flushed[stream]);
+            ShortCircuit::Known(matcher.finish())
+        }
+        Predicate::Content(ContentState::Near(mut matcher, stream)) => {
+            matcher.feed(&flushed[stream]);
+            ShortCircuit::Known(matcher.finish())
+        }
+        Predicate::Content(ContentState::Hash(pred)) => ShortCircuit::Known(
+            digests
+                .iter()
+                .any(|(algorithm, digest)| *algorithm == pred.algorithm && pred.is_match(digest)),
+        ),
+        Predicate::Content(ContentState::Lines(pred, stream)) => {
+            let lines = streams[stream]
+                .lines
+                .as_ref()
+                .expect("line stats are gathered");
+            ShortCircuit::Known(pred.eval(lines))
+        }
+        Predicate::Content(ContentState::Count(mut counter, matcher, stream)) => {
+            counter.feed(&flushed[stream]);
+            counter.finish();
+            ShortCircuit::Known(matcher.is_match(counter.count()))
         }
         _ => unreachable!(),
     });
 
     Ok(e)
 }
+
+/// Feed `bytes` to a DFA, returning the next state, or `Err` with the result
+/// once a match or dead state decides it
+fn advance_dfa(dfa: &DFA<Vec<u32>>, state: StateID, bytes: &[u8]) -> Result<StateID, bool> {
+    let mut state = state;
+    for byte in bytes {
+        state = dfa.next_state(state, *byte);
+
+        if dfa.is_match_state(state) {
+            return Err(true);
+        }
+
+        if dfa.is_dead_state(state) {
+            return Err(false);
+        }
+    }
+    Ok(state)
+}
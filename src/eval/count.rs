@@ -0,0 +1,184 @@
+//! Counting non-overlapping matches of a content pattern as the file streams
+//! past, for `count(content contains TODO) > 5`
+
+use std::collections::VecDeque;
+
+use regex_automata::dfa::{dense::DFA, Automaton};
+use regex_automata::util::primitives::StateID;
+
+/// Counts leftmost-first matches, restarting the DFA after each one
+///
+/// The DFA only reports a match one byte after it ends, and a match may
+/// still grow, so a match is counted once the DFA dies. Bytes read after
+/// the end of the match are then replayed from the start state for the byte
+/// before them, so `^` and `\b` see where in the file the search restarts.
+/// Patterns must not match empty text, so every replay makes progress.
+#[derive(Clone)]
+pub struct MatchCounter<'a> {
+    dfa: &'a DFA<Vec<u32>>,
+    state: StateID,
+    count: u64,
+    /// A match has been seen and may still grow
+    in_match: bool,
+    /// The byte last read
+    last_byte: Option<u8>,
+    /// The last byte of the current match
+    match_end: Option<u8>,
+    /// Bytes read since the end of the current match
+    pending: Vec<u8>,
+    /// Bytes to read again after a match
+    replay: VecDeque<u8>,
+    /// The DFA died without a match, so no more can be found
+    done: bool,
+}
+
+impl<'a> MatchCounter<'a> {
+    pub fn new(dfa: &'a DFA<Vec<u32>>) -> Self {
+        Self {
+            dfa,
+            state: start_state(dfa, None),
+            count: 0,
+            in_match: false,
+            last_byte: None,
+            match_end: None,
+            pending: Vec::new(),
+            replay: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Matches counted so far
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Whether more input can't change the count
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.step(byte);
+            while let Some(byte) = self.replay.pop_front() {
+                self.step(byte);
+            }
+        }
+    }
+
+    /// Count a match running up to the end of the file, and any left to find
+    /// in the bytes after the last one
+    pub fn finish(&mut self) {
+        while !self.done {
+            let eoi = self.dfa.next_eoi_state(self.state);
+            if self.dfa.is_match_state(eoi) {
+                self.count += 1;
+                return;
+            }
+            if !self.in_match {
+                return;
+            }
+            let pending = self.end_match();
+            self.feed(&pending);
+        }
+    }
+
+    fn step(&mut self, byte: u8) {
+        if self.done {
+            return;
+        }
+        let next = self.dfa.next_state(self.state, byte);
+        let last_byte = self.last_byte.replace(byte);
+        if self.dfa.is_match_state(next) {
+            // A match ends just before this byte
+            self.in_match = true;
+            self.match_end = last_byte;
+            self.pending.clear();
+            self.pending.push(byte);
+            self.state = next;
+        } else if self.dfa.is_dead_state(next) {
+            if self.in_match {
+                let mut pending = self.end_match();
+                pending.push(byte);
+                for byte in pending.into_iter().rev() {
+                    self.replay.push_front(byte);
+                }
+            } else {
+                self.done = true;
+            }
+        } else {
+            if self.in_match {
+                self.pending.push(byte);
+            }
+            self.state = next;
+        }
+    }
+
+    /// Count the current match and restart, returning the bytes after it
+    fn end_match(&mut self) -> Vec<u8> {
+        self.count += 1;
+        self.in_match = false;
+        self.state = start_state(self.dfa, self.match_end);
+        self.last_byte = self.match_end;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// The state to search from after `look_behind`, or from the start of the file
+fn start_state(dfa: &DFA<Vec<u32>>, look_behind: Option<u8>) -> StateID {
+    dfa.start_state(&regex_automata::util::start::Config::new().look_behind(look_behind))
+        .expect("DFA start_state failed: invalid regex configuration")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(pattern: &str, chunks: &[&[u8]]) -> u64 {
+        let dfa = DFA::new(pattern).unwrap();
+        let mut counter = MatchCounter::new(&dfa);
+        for chunk in chunks {
+            counter.feed(chunk);
+        }
+        counter.finish();
+        counter.count()
+    }
+
+    #[test]
+    fn test_literal_matches() {
+        assert_eq!(count("TODO", &[b"TODO TODO\nTODOTODO"]), 4);
+        assert_eq!(count("TODO", &[b"TO", b"DO x TOD", b"O"]), 2);
+        assert_eq!(count("TODO", &[b"nothing here"]), 0);
+        assert_eq!(count(r"unwrap\(\)", &[b"a.unwrap().unwrap()"]), 2);
+    }
+
+    #[test]
+    fn test_matches_are_leftmost_and_non_overlapping() {
+        // Greedy repetition is one match, not one per byte
+        assert_eq!(count("a+", &[b"aaa b aa"]), 2);
+        assert_eq!(count("aa", &[b"aaaaa"]), 2);
+        assert_eq!(count(r"\w+", &[b"one two", b" three"]), 3);
+        // Bytes read past the end of a match are searched again
+        assert_eq!(count("ab|b", &[b"abb"]), 2);
+    }
+
+    #[test]
+    fn test_anchored_pattern_stops() {
+        let dfa = DFA::new("^#!").unwrap();
+        let mut counter = MatchCounter::new(&dfa);
+        counter.feed(b"x #!");
+        assert!(counter.is_done());
+        counter.finish();
+        assert_eq!(counter.count(), 0);
+    }
+
+    #[test]
+    fn test_restart_keeps_look_behind() {
+        // `^` doesn't match again where the last match ended
+        assert_eq!(count("^#", &[b"##x"]), 1);
+        assert_eq!(count("^#+", &[b"##x#"]), 1);
+        assert_eq!(count(r"(?-u:\b)foo", &[b"foofoo"]), 1);
+        assert_eq!(count(r"(?-u:\b)foo", &[b"foo", b" foo"]), 2);
+        assert_eq!(count(r"foo(?-u:\b)", &[b"foo foo"]), 2);
+    }
+}
@@ -1,36 +1,42 @@
 use crate::expr::short_circuit::ShortCircuit;
 use crate::expr::Expr;
 use crate::predicate::{
-    MetadataPredicate, NamePredicate, Predicate, StreamingCompiledContentPredicateRef,
+    ContentDecoder, ContentPredicateRef, LineWindow, MetadataPredicate, NamePredicate, Predicate,
+    TextEncoding, HEADER_SIZE,
 };
 use crate::util::Done;
 use futures::{stream, TryStreamExt};
 use slog::{debug, o, Logger};
+use std::io::SeekFrom;
 use std::path::Path;
 use tokio::fs::File;
-use tokio::io::BufStream;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, BufStream};
 use tokio_util::io::ReaderStream;
 
+use crate::eval::line_match::LineMatcher;
 use crate::eval::run_contents_predicate_stream;
 use crate::eval::structured::{eval_structured_predicate, ParsedDocuments};
+use crate::eval::tree::eval_tree_predicates;
 
 /// multipass evaluation with short circuiting, runs, in order:
 /// - file name matchers
 /// - metadata matchers
+/// - file header matchers
+/// - windows at the end of the file, like `tail(5)`
 /// - file content matchers
 pub async fn eval<'dfa>(
     logger: &Logger,
-    e: &'dfa Expr<
-        Predicate<NamePredicate, MetadataPredicate, StreamingCompiledContentPredicateRef<'dfa>>,
-    >,
+    e: &'dfa Expr<Predicate<NamePredicate, MetadataPredicate, ContentPredicateRef<'dfa>>>,
     path: &Path,
     base_path: Option<&Path>,
+    respect_gitignore: bool,
+    one_file_system: bool,
 ) -> std::io::Result<bool> {
     let logger = logger.new(o!("path" => format!("{:?}", path)));
 
     debug!(logger, "visit entity"; "expr" => %e);
 
-    let e: Expr<Predicate<Done, MetadataPredicate, StreamingCompiledContentPredicateRef<'dfa>>> =
+    let e: Expr<Predicate<Done, MetadataPredicate, ContentPredicateRef<'dfa>>> =
         e.reduce_predicate_and_short_circuit(|p| p.eval_name_predicate(path, base_path));
 
     if let Expr::Literal(b) = e {
@@ -40,22 +46,35 @@ pub async fn eval<'dfa>(
 
     debug!(logger, "reduced expr after path predicate eval";  "expr" => %e);
 
-    let file = File::open(path).await?;
+    let mut file = File::open(path).await?;
     let metadata = file.metadata().await?;
 
-    let e: Expr<Predicate<Done, Done, StreamingCompiledContentPredicateRef<'dfa>>> =
-        e.reduce_predicate_and_short_circuit(|p| p.eval_metadata_predicate(&metadata));
+    let e: Expr<Predicate<Done, MetadataPredicate, ContentPredicateRef<'dfa>>> =
+        e.reduce_predicate_and_short_circuit(|p| p.eval_local_metadata_predicate(path, &metadata));
 
     if let Expr::Literal(b) = e {
         debug!(logger, "short circuit after metadata predicate eval";  "expr" => %e, "result" => %b);
         return Ok(b);
     }
 
-    debug!(logger, "reduced expr after metadata predicate eval";  "expr" => %e);
+    // Only tree predicates are left in the metadata family now
+    let e: Expr<Predicate<Done, Done, ContentPredicateRef<'dfa>>> = eval_tree_predicates(
+        &logger,
+        e,
+        path,
+        &metadata,
+        base_path,
+        respect_gitignore,
+        one_file_system,
+    )
+    .await;
 
-    // Determine which predicates remain for optimized file reading
-    let has_structured = e.contains_structured_predicates();
-    let has_content = e.contains_content_predicates();
+    if let Expr::Literal(b) = e {
+        debug!(logger, "short circuit after metadata predicate eval";  "expr" => %e, "result" => %b);
+        return Ok(b);
+    }
+
+    debug!(logger, "reduced expr after metadata predicate eval";  "expr" => %e);
 
     if !metadata.is_file() {
         debug!(
@@ -78,6 +97,93 @@ pub async fn eval<'dfa>(
         unreachable!("all predicates should be reduced to literals after evaluation")
     }
 
+    // Header predicates decided by the file name, like `lang` from an extension
+    let e = e.reduce_predicate_and_short_circuit(|p| match p {
+        Predicate::Content(ContentPredicateRef::Header(h)) => match h.eval_path(path) {
+            Some(b) => ShortCircuit::Known(b),
+            None => ShortCircuit::Unknown(p),
+        },
+        p => ShortCircuit::Unknown(p),
+    });
+
+    if let Expr::Literal(b) = e {
+        debug!(logger, "short circuit after path-resolved header predicate eval"; "expr" => %e, "result" => %b);
+        return Ok(b);
+    }
+
+    // Header phase: predicates that only need the first block of the file
+    let e = if e.any_content_predicate(ContentPredicateRef::is_header) {
+        let header = read_header(&mut file).await?;
+        let e = e.reduce_predicate_and_short_circuit(|p| match p {
+            Predicate::Content(ContentPredicateRef::Header(h)) => {
+                ShortCircuit::Known(h.is_match(path, &header))
+            }
+            p => ShortCircuit::Unknown(p),
+        });
+
+        if let Expr::Literal(b) = e {
+            debug!(logger, "short circuit after header predicate eval"; "expr" => %e, "result" => %b);
+            return Ok(b);
+        }
+
+        debug!(logger, "reduced expr after header predicate eval"; "expr" => %e);
+
+        // Later phases read the file from the start
+        file.rewind().await?;
+        e
+    } else {
+        e
+    };
+
+    // Tail phase: windows at the end of the file, read by seeking back from
+    // it rather than through the whole file
+    let e = if e.any_content_predicate(ContentPredicateRef::is_tail) {
+        // The most lines needed from each encoding
+        let mut wanted: Vec<(Option<TextEncoding>, u64)> = Vec::new();
+        for c in e.content_predicates() {
+            if let ContentPredicateRef::Window(pred, LineWindow::Tail(n)) = c {
+                match wanted.iter_mut().find(|(e, _)| *e == pred.encoding) {
+                    Some((_, most)) => *most = (*most).max(*n),
+                    None => wanted.push((pred.encoding, *n)),
+                }
+            }
+        }
+        let mut tails = Vec::with_capacity(wanted.len());
+        for (encoding, n) in wanted {
+            let tail = read_tail(&mut file, metadata.len(), n, encoding).await?;
+            tails.push((encoding, tail));
+        }
+
+        let e = e.reduce_predicate_and_short_circuit(|p| match p {
+            Predicate::Content(ContentPredicateRef::Window(pred, LineWindow::Tail(n))) => {
+                let (_, tail) = tails
+                    .iter()
+                    .find(|(e, _)| *e == pred.encoding)
+                    .expect("tails are read for every encoding");
+                let mut matcher = LineMatcher::new(pred.inner, false);
+                matcher.feed(last_lines(tail, n).unwrap_or(tail));
+                ShortCircuit::Known(matcher.finish())
+            }
+            p => ShortCircuit::Unknown(p),
+        });
+
+        if let Expr::Literal(b) = e {
+            debug!(logger, "short circuit after tail predicate eval"; "expr" => %e, "result" => %b);
+            return Ok(b);
+        }
+
+        debug!(logger, "reduced expr after tail predicate eval"; "expr" => %e);
+
+        file.rewind().await?;
+        e
+    } else {
+        e
+    };
+
+    // Determine which predicates remain for optimized file reading
+    let has_structured = e.contains_structured_predicates();
+    let has_content = e.contains_content_predicates();
+
     match (has_structured, has_content) {
         (true, true) => {
             debug!(
@@ -221,3 +327,73 @@ pub async fn eval<'dfa>(
         }
     }
 }
+
+/// Read up to `HEADER_SIZE` bytes from the start of a file
+async fn read_header(file: &mut File) -> std::io::Result<Vec<u8>> {
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    file.take(HEADER_SIZE as u64)
+        .read_to_end(&mut header)
+        .await?;
+    Ok(header)
+}
+
+/// Size of the first block read back from the end of a file for `tail`,
+/// doubled each time more is needed. Even, so UTF-16 stays aligned.
+const TAIL_BLOCK: u64 = 8192;
+
+/// Read the last `n` lines of a file, decoded from `encoding` or the file's
+/// BOM, seeking back from the end until they've all been read
+async fn read_tail(
+    file: &mut File,
+    len: u64,
+    n: u64,
+    encoding: Option<TextEncoding>,
+) -> std::io::Result<Vec<u8>> {
+    let encoding = match encoding {
+        Some(encoding) => encoding,
+        None => {
+            file.rewind().await?;
+            let mut bom = Vec::with_capacity(4);
+            file.take(4).read_to_end(&mut bom).await?;
+            TextEncoding::from_bom(&bom).unwrap_or(TextEncoding::Utf8)
+        }
+    };
+
+    let mut raw = Vec::new();
+    let mut start = len;
+    let mut block = TAIL_BLOCK;
+    loop {
+        let end = start;
+        start = start.saturating_sub(block) & !1;
+        block *= 2;
+
+        file.seek(SeekFrom::Start(start)).await?;
+        let mut chunk = vec![0; (end - start) as usize];
+        file.read_exact(&mut chunk).await?;
+        chunk.extend_from_slice(&raw);
+        raw = chunk;
+
+        // The start of a block may cut a character, but only lines after the
+        // first line ending in it are kept
+        let decoded = ContentDecoder::new(Some(encoding)).decode(&raw, true);
+        if start == 0 {
+            return Ok(decoded.into_owned());
+        }
+        if let Some(lines) = last_lines(&decoded, n) {
+            return Ok(lines.to_vec());
+        }
+    }
+}
+
+/// The last `n` lines of `text`, or `None` if it may not hold all of them
+fn last_lines(text: &[u8], n: u64) -> Option<&[u8]> {
+    // The line ending of the last line doesn't start another
+    let body = text.strip_suffix(b"\n").unwrap_or(text);
+    let skip = usize::try_from(n).ok()?.checked_sub(1)?;
+    body.iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, &byte)| byte == b'\n')
+        .nth(skip)
+        .map(|(i, _)| &text[i + 1..])
+}
@@ -0,0 +1,377 @@
+//! Running content patterns over each line as the file streams past, for
+//! `line ~= "^use "`, `all_lines ~= "^\s*//"`, `head(20) contains Copyright`
+//! and `near("unsafe", "// SAFETY:", 3 lines)`
+
+use regex_automata::dfa::{dense::DFA, Automaton};
+use regex_automata::util::primitives::StateID;
+use std::ops::Range;
+
+/// Receives the lines of a file one byte at a time from a [`LineReader`]
+pub trait LineSink {
+    /// The next byte of the current line
+    fn step(&mut self, byte: u8);
+    /// The current line has ended
+    fn end_line(&mut self);
+    /// The result, once the lines so far decide it
+    fn result(&self) -> Option<bool>;
+    /// The result once the whole file has been seen
+    fn finish(&self) -> bool;
+}
+
+/// Splits a file into lines for a [`LineSink`]
+///
+/// Lines are split as for `lines`: they end at `\n`, the `\r` of a CRLF isn't
+/// part of the line, and a last line without a line ending still counts.
+#[derive(Clone)]
+pub struct LineReader<S> {
+    sink: S,
+    /// The current line has started
+    in_line: bool,
+    /// A `\r` that may start a CRLF, decided by the next byte
+    pending_cr: bool,
+}
+
+impl<S: LineSink> LineReader<S> {
+    fn with_sink(sink: S) -> Self {
+        Self {
+            sink,
+            in_line: false,
+            pending_cr: false,
+        }
+    }
+
+    /// The result, once the lines so far decide it
+    pub fn result(&self) -> Option<bool> {
+        self.sink.result()
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if self.sink.result().is_some() {
+                return;
+            }
+            if std::mem::take(&mut self.pending_cr) {
+                if byte == b'\n' {
+                    self.end_line();
+                    continue;
+                }
+                // A lone `\r` stays in the line
+                self.step(b'\r');
+            }
+            match byte {
+                b'\n' => self.end_line(),
+                b'\r' => self.pending_cr = true,
+                _ => self.step(byte),
+            }
+        }
+    }
+
+    /// Account for the last line and decide, once the whole file has been seen
+    pub fn finish(&mut self) -> bool {
+        if std::mem::take(&mut self.pending_cr) {
+            self.step(b'\r');
+        }
+        if self.in_line {
+            self.end_line();
+        }
+        self.sink.finish()
+    }
+
+    fn step(&mut self, byte: u8) {
+        self.in_line = true;
+        self.sink.step(byte);
+    }
+
+    fn end_line(&mut self) {
+        self.in_line = false;
+        self.sink.end_line();
+    }
+}
+
+/// A pattern run over one line at a time, restarting the DFA at every line so
+/// `^` and `$` anchor at line boundaries
+#[derive(Clone)]
+struct LinePattern<'a> {
+    dfa: &'a DFA<Vec<u32>>,
+    start: StateID,
+    state: StateID,
+    /// The current line has matched or can no longer match
+    matched: Option<bool>,
+}
+
+impl<'a> LinePattern<'a> {
+    fn new(dfa: &'a DFA<Vec<u32>>) -> Self {
+        let start = dfa
+            .start_state(&regex_automata::util::start::Config::new())
+            .expect("DFA start_state failed: invalid regex configuration");
+        Self {
+            dfa,
+            start,
+            state: start,
+            matched: None,
+        }
+    }
+
+    fn step(&mut self, byte: u8) {
+        if self.matched.is_some() {
+            return;
+        }
+        let next = self.dfa.next_state(self.state, byte);
+        if self.dfa.is_match_state(next) {
+            self.matched = Some(true);
+        } else if self.dfa.is_dead_state(next) {
+            self.matched = Some(false);
+        } else {
+            self.state = next;
+        }
+    }
+
+    /// Whether the line that just ended matched, restarting for the next
+    fn end_line(&mut self) -> bool {
+        let matched = self
+            .matched
+            .take()
+            .unwrap_or_else(|| self.dfa.is_match_state(self.dfa.next_eoi_state(self.state)));
+        self.state = self.start;
+        matched
+    }
+}
+
+/// `line ~= ...`, `all_lines ~= ...` and windows from the start of the file
+#[derive(Clone)]
+pub struct LineMatch<'a> {
+    pattern: LinePattern<'a>,
+    /// Every line must match, rather than any one
+    all: bool,
+    /// Zero-based indexes of the lines to match
+    window: Range<u64>,
+    /// Index of the current line
+    line: u64,
+    result: Option<bool>,
+}
+
+/// Matches a pattern against any or every line of a file
+pub type LineMatcher<'a> = LineReader<LineMatch<'a>>;
+
+impl<'a> LineMatcher<'a> {
+    pub fn new(dfa: &'a DFA<Vec<u32>>, all: bool) -> Self {
+        Self::with_sink(LineMatch {
+            pattern: LinePattern::new(dfa),
+            all,
+            window: 0..u64::MAX,
+            line: 0,
+            result: None,
+        })
+    }
+
+    /// Only match the lines in `window`, deciding once past it
+    pub fn with_window(mut self, window: Range<u64>) -> Self {
+        if window.is_empty() {
+            self.sink.result = Some(self.sink.all);
+        }
+        self.sink.window = window;
+        self
+    }
+}
+
+impl LineMatch<'_> {
+    fn decide_line(&mut self, matched: bool) {
+        if matched != self.all {
+            self.result = Some(matched);
+        }
+    }
+}
+
+impl LineSink for LineMatch<'_> {
+    fn step(&mut self, byte: u8) {
+        if !self.window.contains(&self.line) {
+            return;
+        }
+        self.pattern.step(byte);
+        if let Some(matched) = self.pattern.matched {
+            self.decide_line(matched);
+        }
+    }
+
+    fn end_line(&mut self) {
+        let matched = self.pattern.end_line();
+        if self.window.contains(&self.line) {
+            self.decide_line(matched);
+        }
+        self.line += 1;
+        if self.line >= self.window.end && self.result.is_none() {
+            // No line left in the window to change the result
+            self.result = Some(self.all);
+        }
+    }
+
+    fn result(&self) -> Option<bool> {
+        self.result
+    }
+
+    fn finish(&self) -> bool {
+        // No line matched, or none failed to
+        self.result.unwrap_or(self.all)
+    }
+}
+
+/// `near(a, b, N lines)`: lines matching each pattern at most `distance` lines
+/// apart, in either order
+#[derive(Clone)]
+pub struct Near<'a> {
+    first: LinePattern<'a>,
+    second: LinePattern<'a>,
+    distance: u64,
+    /// Index of the current line
+    line: u64,
+    /// Last lines each pattern matched
+    last_first: Option<u64>,
+    last_second: Option<u64>,
+    found: bool,
+}
+
+/// Matches two patterns on nearby lines
+pub type NearMatcher<'a> = LineReader<Near<'a>>;
+
+impl<'a> NearMatcher<'a> {
+    pub fn new(first: &'a DFA<Vec<u32>>, second: &'a DFA<Vec<u32>>, distance: u64) -> Self {
+        Self::with_sink(Near {
+            first: LinePattern::new(first),
+            second: LinePattern::new(second),
+            distance,
+            line: 0,
+            last_first: None,
+            last_second: None,
+            found: false,
+        })
+    }
+}
+
+impl Near<'_> {
+    /// Record which patterns matched the current line. The last match of the
+    /// other pattern is the closest one seen.
+    fn record(&mut self, first: bool, second: bool) {
+        let line = self.line;
+        let close = |last: Option<u64>| last.is_some_and(|last| line - last <= self.distance);
+        if first {
+            self.found |= close(self.last_second);
+            self.last_first = Some(line);
+        }
+        if second {
+            self.found |= close(self.last_first);
+            self.last_second = Some(line);
+        }
+    }
+}
+
+impl LineSink for Near<'_> {
+    fn step(&mut self, byte: u8) {
+        self.first.step(byte);
+        self.second.step(byte);
+        let first = self.first.matched == Some(true);
+        let second = self.second.matched == Some(true);
+        self.record(first, second);
+    }
+
+    fn end_line(&mut self) {
+        let first = self.first.end_line();
+        let second = self.second.end_line();
+        self.record(first, second);
+        self.line += 1;
+    }
+
+    fn result(&self) -> Option<bool> {
+        self.found.then_some(true)
+    }
+
+    fn finish(&self) -> bool {
+        self.found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn any_line(pattern: &str, chunks: &[&[u8]]) -> bool {
+        run(pattern, chunks, false)
+    }
+
+    fn all_lines(pattern: &str, chunks: &[&[u8]]) -> bool {
+        run(pattern, chunks, true)
+    }
+
+    fn run(pattern: &str, chunks: &[&[u8]], all: bool) -> bool {
+        let dfa = DFA::new(pattern).unwrap();
+        let mut matcher = LineMatcher::new(&dfa, all);
+        feed(&mut matcher, chunks)
+    }
+
+    fn feed<S: LineSink>(reader: &mut LineReader<S>, chunks: &[&[u8]]) -> bool {
+        for chunk in chunks {
+            reader.feed(chunk);
+        }
+        reader.finish()
+    }
+
+    fn near(first: &str, second: &str, distance: u64, text: &[u8]) -> bool {
+        let first = DFA::new(first).unwrap();
+        let second = DFA::new(second).unwrap();
+        feed(&mut NearMatcher::new(&first, &second, distance), &[text])
+    }
+
+    #[test]
+    fn test_anchors_are_per_line() {
+        assert!(any_line("^b$", &[b"a\nb\nc\n"]));
+        assert!(!any_line("^b$", &[b"a\nbb\nc\n"]));
+        assert!(any_line("^use ", &[b"// x\n", b"use std;\n"]));
+        // The `\r` of a CRLF isn't part of the line
+        assert!(any_line("^b$", &[b"a\r\nb\r", b"\n"]));
+        // A last line without a line ending still counts
+        assert!(any_line("^c$", &[b"a\nc"]));
+    }
+
+    #[test]
+    fn test_all_lines() {
+        assert!(all_lines(r"^\s*//", &[b"// a\n  // b\n"]));
+        assert!(!all_lines(r"^\s*//", &[b"// a\ncode\n// b\n"]));
+        // An empty line is still a line
+        assert!(!all_lines("x", &[b"x\n\nx\n"]));
+        // A file without lines has none that fail
+        assert!(all_lines("x", &[b""]));
+        assert!(!any_line("x", &[b""]));
+    }
+
+    #[test]
+    fn test_window() {
+        let dfa = DFA::new("^x$").unwrap();
+        let in_window = |window: Range<u64>, text: &[u8]| {
+            let mut matcher = LineMatcher::new(&dfa, false).with_window(window);
+            feed(&mut matcher, &[text])
+        };
+        assert!(in_window(1..3, b"a\nx\nb\n"));
+        assert!(!in_window(0..1, b"a\nx\nb\n"));
+        assert!(!in_window(2..4, b"a\nx\nb\n"));
+        assert!(in_window(0..2, b"a\r\nx"));
+
+        // Decided once past the window, without the rest of the file
+        let mut matcher = LineMatcher::new(&dfa, false).with_window(0..2);
+        matcher.feed(b"a\nb\nx\n");
+        assert_eq!(matcher.result(), Some(false));
+    }
+
+    #[test]
+    fn test_near() {
+        let text = b"unsafe {\n    x\n}\n// SAFETY: fine\n";
+        assert!(near("unsafe", "SAFETY:", 3, text));
+        assert!(!near("unsafe", "SAFETY:", 2, text));
+        // In either order
+        assert!(near("SAFETY:", "unsafe", 3, text));
+        // On the same line
+        assert!(near("a", "b", 0, b"x\nab\n"));
+        assert!(!near("a", "b", 0, b"a\nb\n"));
+        // The closest earlier match counts
+        assert!(near("a", "b", 1, b"a\n\n\n\na\nb"));
+        assert!(!near("a", "b", 5, b"a\n"));
+    }
+}
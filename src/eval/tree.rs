@@ -0,0 +1,294 @@
+//! Evaluation of tree predicates, which run a sub-expression against other
+//! entries (children, descendants) of the entry being visited
+
+use std::fs::Metadata;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::future::{BoxFuture, FutureExt};
+use slog::{debug, Logger};
+
+use crate::eval::borrow_expr;
+use crate::expr::short_circuit::ShortCircuit;
+use crate::expr::Expr;
+use crate::predicate::{
+    ContentPredicateRef, MetadataPredicate, NamePredicate, Predicate, TreePredicate, TreeRelation,
+    TreeTotals,
+};
+use crate::util::Done;
+
+type BorrowedExpr<'a> = Expr<Predicate<NamePredicate, MetadataPredicate, ContentPredicateRef<'a>>>;
+
+/// Resolve the tree predicates left over after the synchronous metadata pass
+pub async fn eval_tree_predicates<C: Clone, S: Clone>(
+    logger: &Logger,
+    e: Expr<Predicate<Done, MetadataPredicate, C, S>>,
+    path: &Path,
+    metadata: &Metadata,
+    base_path: Option<&Path>,
+    respect_gitignore: bool,
+    one_file_system: bool,
+) -> Expr<Predicate<Done, Done, C, S>> {
+    let mut results = Vec::new();
+    for predicate in e.metadata_predicates() {
+        let result = match predicate.as_ref() {
+            MetadataPredicate::Tree(tree) => {
+                eval_tree_predicate(
+                    logger,
+                    tree,
+                    path,
+                    metadata,
+                    base_path,
+                    respect_gitignore,
+                    one_file_system,
+                )
+                .await
+            }
+            other => unreachable!("only tree predicates remain after metadata pass: {other:?}"),
+        };
+        results.push((Arc::clone(predicate), result));
+    }
+
+    e.reduce_predicate_and_short_circuit(|p| match p {
+        Predicate::Metadata(m) => ShortCircuit::Known(
+            results
+                .iter()
+                .any(|(predicate, result)| Arc::ptr_eq(predicate, &m) && *result),
+        ),
+        Predicate::Content(c) => ShortCircuit::Unknown(Predicate::Content(c)),
+        Predicate::Structured(s) => ShortCircuit::Unknown(Predicate::Structured(s)),
+        Predicate::Name(_) => unreachable!("name predicates are resolved before metadata"),
+    })
+}
+
+/// Resolve the tree aggregate predicates of `e` before evaluation: against
+/// `totals` for a finished directory, or to false for anything else
+pub fn resolve_tree_totals<C: Clone, S: Clone>(
+    e: &Expr<Predicate<NamePredicate, MetadataPredicate, C, S>>,
+    totals: Option<(&TreeTotals, &Metadata)>,
+) -> Expr<Predicate<NamePredicate, MetadataPredicate, C, S>> {
+    e.reduce_predicate_and_short_circuit(|p| match p {
+        Predicate::Metadata(m) if m.is_aggregate() => ShortCircuit::Known(
+            totals.is_some_and(|(totals, metadata)| m.is_match_totals(totals, metadata)),
+        ),
+        p => ShortCircuit::Unknown(p),
+    })
+}
+
+/// Evaluate a single tree predicate for the entry at `path`
+pub async fn eval_tree_predicate(
+    logger: &Logger,
+    predicate: &TreePredicate,
+    path: &Path,
+    metadata: &Metadata,
+    base_path: Option<&Path>,
+    respect_gitignore: bool,
+    one_file_system: bool,
+) -> bool {
+    let expr = borrow_expr(&predicate.expr);
+    let ctx = TreeContext {
+        logger,
+        predicate,
+        expr: &expr,
+        base_path,
+        respect_gitignore,
+        one_file_system,
+    };
+
+    match predicate.relation {
+        // Anything that isn't a directory has no children, so never matches
+        TreeRelation::Child | TreeRelation::Descendant(_) if !metadata.is_dir() => false,
+        TreeRelation::Child => ctx.any_below(path.to_path_buf(), Some(1)).await,
+        TreeRelation::Descendant(depth) => ctx.any_below(path.to_path_buf(), depth).await,
+        // Like `Sibling`, the entry itself doesn't count in its own directory
+        TreeRelation::Ancestor => match path.parent() {
+            Some(parent) if ctx.in_scope(parent) => {
+                ctx.matching_children(parent.to_path_buf())
+                    .await
+                    .iter()
+                    .any(|child| child != path)
+                    || match parent.parent() {
+                        Some(above) if ctx.in_scope(above) => {
+                            ctx.any_ancestor(above.to_path_buf()).await
+                        }
+                        _ => false,
+                    }
+            }
+            _ => false,
+        },
+        TreeRelation::Sibling => match path.parent() {
+            Some(parent) => ctx
+                .matching_children(parent.to_path_buf())
+                .await
+                .iter()
+                .any(|child| child != path),
+            None => false,
+        },
+    }
+}
+
+/// Everything needed to evaluate one tree predicate's sub-expression
+struct TreeContext<'a> {
+    logger: &'a Logger,
+    predicate: &'a TreePredicate,
+    expr: &'a BorrowedExpr<'a>,
+    base_path: Option<&'a Path>,
+    /// Listings follow the main walk's gitignore setting
+    respect_gitignore: bool,
+    /// Directories on other filesystems than the search root aren't listed
+    one_file_system: bool,
+}
+
+impl<'a> TreeContext<'a> {
+    /// Directories outside the search root are never listed
+    fn in_scope(&self, dir: &Path) -> bool {
+        self.base_path.map_or(true, |base| dir.starts_with(base))
+    }
+
+    /// Whether any entry below `dir`, at most `depth` levels down, matches
+    ///
+    /// Only real directories are descended into, so symlink loops can't recurse.
+    fn any_below(&'a self, dir: PathBuf, depth: Option<u32>) -> BoxFuture<'a, bool> {
+        async move {
+            if depth == Some(0) {
+                return false;
+            }
+            if let Some(&result) = self.predicate.memo().subtrees.get(&(dir.clone(), depth)) {
+                return result;
+            }
+
+            let mut result = false;
+            for (child, is_dir) in self.list(&dir).await {
+                if self.matches(&child).await
+                    || (is_dir
+                        && depth != Some(1)
+                        && self.any_below(child, depth.map(|d| d - 1)).await)
+                {
+                    result = true;
+                    break;
+                }
+            }
+
+            self.predicate.memo().subtrees.insert((dir, depth), result);
+            result
+        }
+        .boxed()
+    }
+
+    /// Whether `dir` or any directory above it, up to the search root, has
+    /// a matching entry
+    fn any_ancestor(&'a self, dir: PathBuf) -> BoxFuture<'a, bool> {
+        async move {
+            if let Some(&result) = self.predicate.memo().ancestors.get(&dir) {
+                return result;
+            }
+
+            let result = self.any_below(dir.clone(), Some(1)).await
+                || match dir.parent() {
+                    Some(parent) if self.in_scope(parent) => {
+                        self.any_ancestor(parent.to_path_buf()).await
+                    }
+                    _ => false,
+                };
+
+            self.predicate.memo().ancestors.insert(dir, result);
+            result
+        }
+        .boxed()
+    }
+
+    /// Up to two matching entries of `dir`
+    async fn matching_children(&self, dir: PathBuf) -> Vec<PathBuf> {
+        if let Some(children) = self.predicate.memo().children.get(&dir) {
+            return children.clone();
+        }
+
+        let mut matching = Vec::new();
+        for (child, _) in self.list(&dir).await {
+            if self.matches(&child).await {
+                matching.push(child);
+                if matching.len() == 2 {
+                    break;
+                }
+            }
+        }
+
+        self.predicate.memo().children.insert(dir, matching.clone());
+        matching
+    }
+
+    /// Entries of `dir` and whether each is a real directory, filtered like
+    /// the main walk so VCS directories and ignored entries are skipped
+    async fn list(&self, dir: &Path) -> Vec<(PathBuf, bool)> {
+        let mut children = Vec::new();
+        if self.on_other_file_system(dir) {
+            return children;
+        }
+        for entry in crate::walk_builder(dir, self.respect_gitignore)
+            .max_depth(Some(1))
+            .build()
+        {
+            match entry {
+                Ok(entry) if entry.depth() == 1 => {
+                    let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                    children.push((entry.into_path(), is_dir));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    debug!(self.logger, "can't list directory for tree predicate"; "dir" => #?dir, "error" => %e);
+                }
+            }
+        }
+        children
+    }
+
+    /// Whether `dir` is on another filesystem than the search root under `-x`,
+    /// so the main walk doesn't descend into it
+    fn on_other_file_system(&self, dir: &Path) -> bool {
+        let (true, Some(base_path)) = (self.one_file_system, self.base_path) else {
+            return false;
+        };
+        match (std::fs::metadata(dir), std::fs::metadata(base_path)) {
+            (Ok(dir), Ok(base)) => dir.dev() != base.dev(),
+            _ => false,
+        }
+    }
+
+    /// Evaluate the sub-expression for one entry, memoized per path
+    ///
+    /// This recurses into `eval::fs::eval`, so the future is boxed.
+    fn matches(&'a self, path: &'a Path) -> BoxFuture<'a, bool> {
+        async move {
+            if let Some(&result) = self.predicate.memo().matches.get(path) {
+                return result;
+            }
+
+            let result =
+                match crate::eval::fs::eval(
+                    self.logger,
+                    self.expr,
+                    path,
+                    self.base_path,
+                    self.respect_gitignore,
+                    self.one_file_system,
+                )
+                .await
+                {
+                    Ok(result) => result,
+                    Err(e) => {
+                        // Entries we can't read don't match, same as in the main walk
+                        debug!(self.logger, "skipping entry in tree predicate"; "path" => #?path, "error" => %e);
+                        false
+                    }
+                };
+
+            self.predicate
+                .memo()
+                .matches
+                .insert(path.to_path_buf(), result);
+            result
+        }
+        .boxed()
+    }
+}
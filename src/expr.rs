@@ -57,6 +57,42 @@ impl<Name, Meta, Content, Structured> Expr<Predicate<Name, Meta, Content, Struct
         })
     }
 
+    /// Collect the Metadata predicates in this expression, left to right
+    pub fn metadata_predicates(&self) -> Vec<&Arc<Meta>> {
+        MapPredicateRef(self).collapse_frames(|e: ExprFrame<Vec<_>, _>| match e {
+            ExprFrame::Predicate(Predicate::Metadata(m)) => vec![m],
+            ExprFrame::And(mut a, b) | ExprFrame::Or(mut a, b) => {
+                a.extend(b);
+                a
+            }
+            ExprFrame::Not(a) => a,
+            ExprFrame::Predicate(_) | ExprFrame::Literal(_) => Vec::new(),
+        })
+    }
+
+    /// Collect the Content predicates in this expression, left to right
+    pub fn content_predicates(&self) -> Vec<&Content> {
+        MapPredicateRef(self).collapse_frames(|e: ExprFrame<Vec<_>, _>| match e {
+            ExprFrame::Predicate(Predicate::Content(c)) => vec![c],
+            ExprFrame::And(mut a, b) | ExprFrame::Or(mut a, b) => {
+                a.extend(b);
+                a
+            }
+            ExprFrame::Not(a) => a,
+            ExprFrame::Predicate(_) | ExprFrame::Literal(_) => Vec::new(),
+        })
+    }
+
+    /// Check if any Content predicate in the expression satisfies `f`
+    pub fn any_content_predicate(&self, f: impl Fn(&Content) -> bool) -> bool {
+        MapPredicateRef(self).collapse_frames(|e| match e {
+            ExprFrame::Predicate(Predicate::Content(c)) => f(c),
+            ExprFrame::And(a, b) | ExprFrame::Or(a, b) => a || b,
+            ExprFrame::Not(a) => a,
+            ExprFrame::Predicate(_) | ExprFrame::Literal(_) => false,
+        })
+    }
+
     /// Check if expression contains any Content predicates
     pub fn contains_content_predicates(&self) -> bool {
         MapPredicateRef(self).collapse_frames(|e| match e {
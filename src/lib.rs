@@ -2,6 +2,7 @@
 #![warn(clippy::all)]
 #![warn(clippy::cargo)]
 
+pub mod duplicates;
 pub mod eval;
 pub mod expr;
 pub mod parser;
@@ -9,11 +10,21 @@ pub mod predicate;
 mod predicate_error;
 pub mod util;
 
-use std::{path::Path, sync::Arc, time::Instant};
+use std::{
+    collections::HashSet,
+    fs::Metadata,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
+use expr::Expr;
 use ignore::WalkBuilder;
 use parser::{error::DetectError, RawParser, Typechecker};
-use predicate::Predicate;
+use predicate::{
+    ContentPredicateRef, FileTypeDefs, MetadataPredicate, NamePredicate, Predicate, TextEncoding,
+    TreeTotals,
+};
 use slog::{debug, info, warn, Logger};
 
 /// Runtime configuration for detect operations
@@ -22,12 +33,61 @@ pub struct RuntimeConfig {
     /// Maximum file size (in bytes) for structured data parsing (YAML/JSON/TOML)
     /// Files larger than this will skip structured data evaluation
     pub max_structured_size: u64,
+    /// Report each physical file once, skipping further hard links to an
+    /// inode already matched (the first matching link in traversal order wins)
+    pub unique_inodes: bool,
+    /// Don't descend into directories on other filesystems than the root
+    pub one_file_system: bool,
+    /// How content patterns and the `text`/`binary` aliases treat files that
+    /// look binary
+    pub binary: BinaryPolicy,
+    /// Named glob sets for `ftype`: the built-in table plus any from the
+    /// config file
+    pub file_types: FileTypeDefs,
+    /// Encoding to decode every file from before content patterns see it, or
+    /// `None` to sniff each file for a byte order mark
+    pub encoding: Option<TextEncoding>,
 }
 
 impl Default for RuntimeConfig {
     fn default() -> Self {
         Self {
             max_structured_size: 10 * 1024 * 1024, // 10MB default
+            unique_inodes: false,
+            one_file_system: false,
+            binary: BinaryPolicy::default(),
+            file_types: FileTypeDefs::default(),
+            encoding: None,
+        }
+    }
+}
+
+/// Handling of binary files, like ripgrep's `--binary`
+///
+/// A file is binary if its first block contains a NUL byte or isn't valid
+/// UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BinaryPolicy {
+    /// Content patterns never match binary files
+    Skip,
+    /// Content patterns search binary files like any other
+    #[default]
+    Search,
+    /// Every file is text: `binary` never matches and `text` matches all files
+    Text,
+}
+
+impl std::str::FromStr for BinaryPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(BinaryPolicy::Skip),
+            "search" => Ok(BinaryPolicy::Search),
+            "text" => Ok(BinaryPolicy::Text),
+            _ => Err(format!(
+                "Invalid binary policy '{s}'\nValid options: skip, search, text"
+            )),
         }
     }
 }
@@ -57,29 +117,22 @@ pub async fn parse_and_run_fs<F: FnMut(&Path)>(
                 });
             }
 
-            let walker = WalkBuilder::new(root)
-                .hidden(false)
-                .git_ignore(respect_gitignore)
-                .filter_entry(|entry| {
-                    // Always exclude VCS directories, regardless of gitignore settings
-                    // This matches ripgrep's behavior
-                    !entry
-                        .file_name()
-                        .to_str()
-                        .is_some_and(|s| s == ".git" || s == ".hg" || s == ".svn")
-                })
+            let walker = walk_builder(root, respect_gitignore)
+                .same_file_system(config.one_file_system)
                 .build();
 
-            let expr = parsed_expr.map_predicate_ref(|p| match p {
-                Predicate::Name(n) => Predicate::Name(Arc::clone(n)),
-                Predicate::Metadata(m) => Predicate::Metadata(Arc::clone(m)),
-                Predicate::Content(c) => Predicate::Content(c.as_ref()),
-                Predicate::Structured(s) => Predicate::Structured(s.clone()),
-            });
+            let expr = eval::borrow_expr(&parsed_expr);
 
             info!(logger, "parsed expression"; "expr" => %expr);
 
+            // Tree aggregates are only known once everything below a directory
+            // has been visited, so directories are decided after their contents
+            let aggregates = expr.metadata_predicates().iter().any(|m| m.is_aggregate());
+            let mut open_dirs: Vec<OpenDir> = Vec::new();
+
             let mut match_count = 0;
+            let mut seen_inodes = HashSet::new();
+            let mut counted_inodes = HashSet::new();
             for entry in walker {
                 let entry = match entry {
                     Ok(e) => e,
@@ -95,32 +148,114 @@ pub async fn parse_and_run_fs<F: FnMut(&Path)>(
                     continue;
                 }
 
-                let start = Instant::now();
+                // Only multiply-linked files can repeat, so only those are
+                // tracked
+                let hard_link = entry
+                    .metadata()
+                    .ok()
+                    .filter(|m| !m.is_dir() && m.nlink() > 1)
+                    .map(|m| (m.dev(), m.ino()));
+                // An inode counts as seen once one of its links matches, so a
+                // non-matching link doesn't hide the others
+                let inode = hard_link.filter(|_| config.unique_inodes);
+                let seen = inode.is_some_and(|inode| seen_inodes.contains(&inode));
+                if seen {
+                    debug!(logger, "skipping already matched inode"; "path" => #?path);
+                }
 
-                let is_match = match eval::fs::eval(&logger, &expr, path, Some(root)).await {
-                    Ok(result) => result,
-                    Err(e) => {
-                        // Handle I/O errors gracefully - skip files we can't access
-                        if e.kind() == std::io::ErrorKind::PermissionDenied {
-                            debug!(logger, "skipping file due to permission denied"; "path" => #?path);
-                            continue;
-                        }
-                        // For other I/O errors, also skip but log at warning level
-                        warn!(logger, "skipping file due to I/O error"; "path" => #?path, "error" => %e);
-                        continue;
+                if !aggregates {
+                    if !seen
+                        && visit(
+                            &logger,
+                            &expr,
+                            path,
+                            root,
+                            respect_gitignore,
+                            config.one_file_system,
+                        )
+                        .await
+                    {
+                        seen_inodes.extend(inode);
+                        match_count += 1;
+                        on_match(path);
                     }
-                };
+                    continue;
+                }
 
-                let duration = start.elapsed();
+                // The walk is depth-first, so open directories that don't
+                // contain this entry are complete
+                while open_dirs
+                    .last()
+                    .is_some_and(|dir| !path.starts_with(&dir.path))
+                {
+                    let dir = open_dirs.pop().expect("checked by the loop condition");
+                    if dir
+                        .visit(
+                            &logger,
+                            &expr,
+                            root,
+                            respect_gitignore,
+                            config.one_file_system,
+                        )
+                        .await
+                    {
+                        match_count += 1;
+                        on_match(&dir.path);
+                    }
+                }
 
-                debug!(logger, "visited entity"; "path" => #?path, "duration" => #?duration, "result" => is_match);
+                if let Ok(metadata) = entry.metadata() {
+                    // Each physical file adds to the totals once, like `du`,
+                    // matched or not
+                    if hard_link.map_or(true, |inode| counted_inodes.insert(inode)) {
+                        for dir in &mut open_dirs {
+                            dir.totals.add(&metadata);
+                        }
+                    }
+                    if metadata.is_dir() {
+                        open_dirs.push(OpenDir {
+                            path: path.to_path_buf(),
+                            totals: TreeTotals::new(&metadata),
+                            metadata,
+                        });
+                        continue;
+                    }
+                }
 
-                if is_match {
+                let expr = eval::tree::resolve_tree_totals(&expr, None);
+                if !seen
+                    && visit(
+                        &logger,
+                        &expr,
+                        path,
+                        root,
+                        respect_gitignore,
+                        config.one_file_system,
+                    )
+                    .await
+                {
+                    seen_inodes.extend(inode);
                     match_count += 1;
                     on_match(path);
                 }
             }
 
+            while let Some(dir) = open_dirs.pop() {
+                if dir
+                    .visit(
+                        &logger,
+                        &expr,
+                        root,
+                        respect_gitignore,
+                        config.one_file_system,
+                    )
+                    .await
+                {
+                    match_count += 1;
+                    on_match(&dir.path);
+                }
+            }
+
             if match_count == 0 {
                 eprintln!("No files matched the query: {original_query}");
                 eprintln!("Searched in: {}", root.display());
@@ -136,3 +271,92 @@ pub async fn parse_and_run_fs<F: FnMut(&Path)>(
         Err(err) => Err(err),
     }
 }
+
+/// A walker over `root` that filters entries like every search: VCS
+/// directories are always skipped, hidden entries never are, and gitignore
+/// rules apply when `respect_gitignore` is set
+pub(crate) fn walk_builder(root: &Path, respect_gitignore: bool) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(false)
+        .git_ignore(respect_gitignore)
+        .filter_entry(|entry| {
+            // Always exclude VCS directories, regardless of gitignore settings
+            // This matches ripgrep's behavior
+            !entry
+                .file_name()
+                .to_str()
+                .is_some_and(|s| s == ".git" || s == ".hg" || s == ".svn")
+        });
+    builder
+}
+
+/// Evaluate `expr` for one entry, skipping entries that can't be read
+async fn visit(
+    logger: &Logger,
+    expr: &Expr<Predicate<NamePredicate, MetadataPredicate, ContentPredicateRef<'_>>>,
+    path: &Path,
+    root: &Path,
+    respect_gitignore: bool,
+    one_file_system: bool,
+) -> bool {
+    let start = Instant::now();
+
+    let is_match = match eval::fs::eval(
+        logger,
+        expr,
+        path,
+        Some(root),
+        respect_gitignore,
+        one_file_system,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            // Handle I/O errors gracefully - skip files we can't access
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                debug!(logger, "skipping file due to permission denied"; "path" => #?path);
+                return false;
+            }
+            // For other I/O errors, also skip but log at warning level
+            warn!(logger, "skipping file due to I/O error"; "path" => #?path, "error" => %e);
+            return false;
+        }
+    };
+
+    let duration = start.elapsed();
+
+    debug!(logger, "visited entity"; "path" => #?path, "duration" => #?duration, "result" => is_match);
+
+    is_match
+}
+
+/// A directory whose decision waits until the walk has left it
+struct OpenDir {
+    path: PathBuf,
+    metadata: Metadata,
+    totals: TreeTotals,
+}
+
+impl OpenDir {
+    async fn visit(
+        &self,
+        logger: &Logger,
+        expr: &Expr<Predicate<NamePredicate, MetadataPredicate, ContentPredicateRef<'_>>>,
+        root: &Path,
+        respect_gitignore: bool,
+        one_file_system: bool,
+    ) -> bool {
+        let expr = eval::tree::resolve_tree_totals(expr, Some((&self.totals, &self.metadata)));
+        visit(
+            logger,
+            &expr,
+            &self.path,
+            root,
+            respect_gitignore,
+            one_file_system,
+        )
+        .await
+    }
+}
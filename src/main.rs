@@ -1,7 +1,18 @@
-use std::{env::current_dir, io::Write, path::PathBuf, str::FromStr};
+use std::{
+    collections::HashSet,
+    env::current_dir,
+    io::Write,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use clap::Parser;
-use detect::{parse_and_run_fs, RuntimeConfig};
+use detect::{
+    parse_and_run_fs,
+    predicate::{EnumPredicate, FileTypeDefs, TextEncoding},
+    BinaryPolicy, RuntimeConfig,
+};
 use slog::{o, Drain, Level, Logger};
 
 const EXAMPLES: &str = include_str!("../docs/examples.md");
@@ -51,6 +62,27 @@ struct Args {
     /// Supports units: kb, mb, gb (e.g., "10mb", "500kb")
     #[arg(long = "max-structured-size", default_value = "10mb")]
     max_structured_size: String,
+    /// Report hard-linked files once, at the first link that matches
+    #[arg(long = "unique-inodes")]
+    unique_inodes: bool,
+    /// Stay on the filesystem of the search root, like `find -xdev`
+    #[arg(short = 'x', long = "one-file-system")]
+    one_file_system: bool,
+    /// Print match count, apparent size and disk usage totals to stderr
+    #[arg(long = "summary")]
+    summary: bool,
+    /// Group matching files with identical contents, printing the groups
+    /// separated by blank lines
+    #[arg(long = "duplicates")]
+    duplicates: bool,
+    /// How to treat binary files: skip (content patterns never match them),
+    /// search (match them like text) or text (treat every file as text)
+    #[arg(long = "binary", value_name = "POLICY", default_value = "search")]
+    binary: String,
+    /// Encoding to decode files from before content patterns see them: auto
+    /// (UTF-16 and UTF-8 byte order marks), utf8, utf16le, utf16be or latin1
+    #[arg(long = "encoding", value_name = "ENCODING", default_value = "auto")]
+    encoding: String,
 }
 
 #[tokio::main]
@@ -93,8 +125,35 @@ pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
             std::process::exit(1);
         });
 
+    let binary = BinaryPolicy::from_str(&args.binary).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    });
+
+    let encoding = match args.encoding.to_lowercase().as_str() {
+        "auto" => None,
+        name => Some(TextEncoding::from_str(name).unwrap_or_else(|_| {
+            eprintln!(
+                "Error: Invalid encoding '{}'\nValid options: auto, {}",
+                args.encoding,
+                TextEncoding::all_valid_strings().join(", ")
+            );
+            std::process::exit(1);
+        })),
+    };
+
+    let file_types = load_file_types().unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    });
+
     let config = RuntimeConfig {
         max_structured_size,
+        unique_inodes: args.unique_inodes,
+        one_file_system: args.one_file_system,
+        binary,
+        file_types,
+        encoding,
     };
 
     let log_level = Level::from_str(&args.log_level).unwrap_or_else(|_| {
@@ -126,34 +185,61 @@ pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap_or_else(|_| root_path.clone());
 
     let mut output = std::io::stdout();
+    let mut totals = Totals::default();
+    // With --duplicates, matches are only printed once grouped
+    let mut matches = Vec::new();
 
     let result = parse_and_run_fs(
-        logger,
+        logger.clone(),
         &root_path,
         !args.visit_gitignored,
         expr,
         config,
         |s| {
-            let display_path = s
-                .strip_prefix(&canonical_root)
-                .unwrap_or(s)
-                .to_string_lossy();
-
-            if let Err(e) = writeln!(output, "./{}", display_path) {
-                if e.kind() == std::io::ErrorKind::BrokenPipe {
-                    // Unix convention: exit 0 on SIGPIPE/BrokenPipe
-                    std::process::exit(0);
-                } else {
-                    eprintln!("Output error: {}", e);
-                    std::process::exit(1);
-                }
+            if args.duplicates {
+                matches.push(s.to_path_buf());
+                return;
+            }
+
+            if args.summary {
+                totals.add(s);
             }
+
+            print_match(&mut output, &canonical_root, s);
         },
     )
     .await;
 
+    let result = result.map(|match_count| {
+        if !args.duplicates {
+            return match_count;
+        }
+
+        let groups = detect::duplicates::find_duplicates(&logger, &matches);
+        for (i, group) in groups.iter().enumerate() {
+            if i > 0 {
+                print_line(&mut output, "");
+            }
+            for path in group {
+                if args.summary {
+                    totals.add(path);
+                }
+                print_match(&mut output, &canonical_root, path);
+            }
+        }
+        groups.iter().map(Vec::len).sum()
+    });
+
     match result {
         Ok(match_count) => {
+            if args.summary {
+                eprintln!(
+                    "{match_count} matches, {} apparent, {} on disk",
+                    detect::util::format_size(totals.apparent),
+                    detect::util::format_size(totals.disk_usage),
+                );
+            }
+
             if match_count > 0 {
                 std::process::exit(0); // Matches found
             } else {
@@ -167,6 +253,81 @@ pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
+/// File types for `ftype`: the built-in table plus the `[types]` of the config
+/// file, if there is one
+///
+/// The config file is `$DETECT_CONFIG`, or else `detect/config.toml` in
+/// `$XDG_CONFIG_HOME` (default `~/.config`).
+fn load_file_types() -> Result<FileTypeDefs, String> {
+    let path = match std::env::var_os("DETECT_CONFIG") {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+                Some(dir) => PathBuf::from(dir),
+                None => match std::env::var_os("HOME") {
+                    Some(home) => PathBuf::from(home).join(".config"),
+                    None => return Ok(FileTypeDefs::default()),
+                },
+            };
+            let path = config_dir.join("detect/config.toml");
+            if !path.exists() {
+                return Ok(FileTypeDefs::default());
+            }
+            path
+        }
+    };
+
+    let source = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read config file {}: {e}", path.display()))?;
+    let mut file_types = FileTypeDefs::default();
+    file_types
+        .extend_from_toml(&source)
+        .map_err(|e| format!("Invalid config file {}: {e}", path.display()))?;
+    Ok(file_types)
+}
+
+/// Print a matching path relative to the search root
+fn print_match(output: &mut impl Write, root: &Path, path: &Path) {
+    let display_path = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
+    print_line(output, &format!("./{display_path}"));
+}
+
+fn print_line(output: &mut impl Write, line: &str) {
+    if let Err(e) = writeln!(output, "{line}") {
+        if e.kind() == std::io::ErrorKind::BrokenPipe {
+            // Unix convention: exit 0 on SIGPIPE/BrokenPipe
+            std::process::exit(0);
+        } else {
+            eprintln!("Output error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Size totals for `--summary`, counting each hard-linked file once like `du`
+#[derive(Default)]
+struct Totals {
+    apparent: u64,
+    disk_usage: u64,
+    seen_inodes: HashSet<(u64, u64)>,
+}
+
+impl Totals {
+    fn add(&mut self, path: &Path) {
+        let Ok(metadata) = std::fs::symlink_metadata(path) else {
+            return;
+        };
+        if metadata.nlink() > 1
+            && !metadata.is_dir()
+            && !self.seen_inodes.insert((metadata.dev(), metadata.ino()))
+        {
+            return;
+        }
+        self.apparent += metadata.size();
+        self.disk_usage += detect::predicate::disk_usage(&metadata);
+    }
+}
+
 struct RuntimeLevelFilter<D> {
     drain: D,
     level: Level,
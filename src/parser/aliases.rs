@@ -3,25 +3,121 @@
 //! Provides shorthand syntax like `dir` instead of `type == dir`,
 //! enabling more natural queries: `dir && depth > 0`
 //!
-//! Also handles structured data selectors like `yaml:.field` as existence predicates.
+//! Also handles structured data selectors like `yaml:.field` as existence predicates,
+//! and property aliases like `executable` (`mode & 0o111`) or `writable`.
 
 use std::sync::Arc;
 
-use super::typed::{parse_structured_selector, AliasError, DataFormat};
+use super::typed::{parse_structured_selector, parse_xattr_selector, AliasError, DataFormat};
 use crate::predicate::{
-    DetectFileType, EnumMatcher, EnumPredicate, MetadataPredicate, Predicate,
-    StructuredDataPredicate,
+    Access, DetectFileType, EnumMatcher, EnumPredicate, HeaderPredicate, InodeFlag, LineCheck,
+    LinesPredicate, MetadataPredicate, NumberMatcher, Predicate, StructuredDataPredicate,
+    XattrPredicate,
 };
 
+/// Property aliases that aren't file types
+const PROPERTY_ALIASES: &[&str] = &[
+    "empty",
+    "executable",
+    "setuid",
+    "setgid",
+    "sticky",
+    "readable",
+    "writable",
+    "orphaned",
+    "hardlinked",
+    "sparse",
+    "mountpoint",
+    "immutable",
+    "append_only",
+    "text",
+    "binary",
+    "bom",
+    "no_final_newline",
+    "trailing_whitespace",
+    "tabs_indent",
+];
+
+/// Resolve a property alias like `executable` to its metadata predicate
+fn resolve_property_alias(word: &str) -> Option<MetadataPredicate> {
+    let predicate = match word.to_lowercase().as_str() {
+        "empty" => MetadataPredicate::Empty,
+        "executable" => MetadataPredicate::Mode(NumberMatcher::AnyBits(0o111)),
+        "setuid" => MetadataPredicate::Mode(NumberMatcher::AnyBits(0o4000)),
+        "setgid" => MetadataPredicate::Mode(NumberMatcher::AnyBits(0o2000)),
+        "sticky" => MetadataPredicate::Mode(NumberMatcher::AnyBits(0o1000)),
+        "readable" => MetadataPredicate::Access(Access::Read),
+        "writable" => MetadataPredicate::Access(Access::Write),
+        "orphaned" => MetadataPredicate::Orphaned,
+        "hardlinked" => MetadataPredicate::Hardlinked,
+        "sparse" => MetadataPredicate::Sparse,
+        "mountpoint" => MetadataPredicate::Mountpoint,
+        "immutable" => MetadataPredicate::Xattr(XattrPredicate::Flag(InodeFlag::Immutable)),
+        "append_only" => MetadataPredicate::Xattr(XattrPredicate::Flag(InodeFlag::AppendOnly)),
+        _ => return None,
+    };
+    Some(predicate)
+}
+
+/// All valid single-word aliases, for error messages
+pub fn all_aliases() -> Vec<&'static str> {
+    DetectFileType::all_valid_strings()
+        .iter()
+        .chain(PROPERTY_ALIASES)
+        .copied()
+        .collect()
+}
+
+/// Resolve a content alias like `binary`, decided from the file's first block
+fn resolve_header_alias(word: &str) -> Option<HeaderPredicate> {
+    match word.to_lowercase().as_str() {
+        "text" => Some(HeaderPredicate::Text),
+        "binary" => Some(HeaderPredicate::Binary),
+        "bom" => Some(HeaderPredicate::Bom),
+        _ => None,
+    }
+}
+
+/// Resolve a line hygiene alias like `trailing_whitespace`, gathered over the
+/// whole file
+fn resolve_lines_alias(word: &str) -> Option<LineCheck> {
+    match word.to_lowercase().as_str() {
+        "no_final_newline" => Some(LineCheck::NoFinalNewline),
+        "trailing_whitespace" => Some(LineCheck::TrailingWhitespace),
+        "tabs_indent" => Some(LineCheck::TabsIndent),
+        _ => None,
+    }
+}
+
 /// Resolve a single-word alias to a predicate
 ///
 /// Supports:
 /// - File type aliases: `file`, `dir`, `symlink`, etc.
 /// - Structured data selectors: `yaml:.field`, `json:.path`, `toml:.key` (existence check)
+/// - Emptiness: `empty` (zero-length file or directory without entries)
+/// - Permission aliases: `executable`, `setuid`, `setgid`, `sticky`, `readable`, `writable`
+/// - Ownership aliases: `orphaned` (uid or gid without a passwd/group entry)
+/// - Link aliases: `hardlinked` (non-directory with `nlink > 1`)
+/// - Allocation aliases: `sparse` (less than half of the apparent size allocated)
+/// - Mount aliases: `mountpoint` (directory listed in `/proc/self/mountinfo`)
+/// - Inode flag aliases: `immutable`, `append_only`
+/// - Content aliases: `text`, `binary` (NUL byte or invalid UTF-8 in the first block), `bom`
+/// - Line aliases: `no_final_newline`, `trailing_whitespace`, `tabs_indent`
+/// - Extended attribute selectors: `xattr:security.capability` (existence check)
 ///
 /// Example: `resolve_alias("dir")` is equivalent to `type == dir`
 /// Example: `resolve_alias("yaml:.spec")` checks if `.spec` exists in YAML file
 pub fn resolve_alias(word: &str) -> Result<Predicate, AliasError> {
+    match parse_xattr_selector(word) {
+        Ok(Some(name)) => {
+            return Ok(Predicate::Metadata(Arc::new(MetadataPredicate::Xattr(
+                XattrPredicate::Exists { name },
+            ))));
+        }
+        Ok(None) => {}
+        Err(e) => return Err(AliasError::Xattr(e)),
+    }
+
     // Check if it's a structured selector
     match parse_structured_selector(word) {
         Ok(Some((format, components))) => {
@@ -41,6 +137,18 @@ pub fn resolve_alias(word: &str) -> Result<Predicate, AliasError> {
         }
     }
 
+    if let Some(predicate) = resolve_property_alias(word) {
+        return Ok(Predicate::Metadata(Arc::new(predicate)));
+    }
+
+    if let Some(predicate) = resolve_header_alias(word) {
+        return Ok(Predicate::contents(predicate));
+    }
+
+    if let Some(check) = resolve_lines_alias(word) {
+        return Ok(Predicate::contents(LinesPredicate::new(check)));
+    }
+
     // Try to resolve as file type alias
     match DetectFileType::from_str(word) {
         Ok(file_type) => Ok(Predicate::Metadata(Arc::new(MetadataPredicate::Type(
@@ -54,13 +162,21 @@ pub fn resolve_alias(word: &str) -> Result<Predicate, AliasError> {
 ///
 /// Uses simple edit distance to find close matches
 pub fn suggest_aliases(word: &str) -> Vec<String> {
-    let all_aliases = DetectFileType::all_valid_strings();
+    suggest_similar(word, all_aliases())
+}
 
-    all_aliases
-        .iter()
-        .filter(|&&alias| levenshtein_distance(word, alias) <= 2)
-        .map(|&s| s.to_string())
-        .collect()
+/// Suggest candidates within a small edit distance of `word`, closest first
+pub(crate) fn suggest_similar<'a>(
+    word: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let mut close: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (levenshtein_distance(word, candidate), candidate))
+        .filter(|&(distance, _)| distance <= 2)
+        .collect();
+    close.sort_by_key(|&(distance, _)| distance);
+    close.into_iter().map(|(_, s)| s.to_string()).collect()
 }
 
 /// Simple Levenshtein distance implementation for fuzzy matching
@@ -118,6 +234,57 @@ mod tests {
         assert!(resolve_alias("chardev").is_ok());
     }
 
+    #[test]
+    fn test_permission_aliases() {
+        assert_eq!(
+            resolve_alias("executable").unwrap(),
+            Predicate::Metadata(Arc::new(MetadataPredicate::Mode(NumberMatcher::AnyBits(
+                0o111
+            ))))
+        );
+        assert!(resolve_alias("setuid").is_ok());
+        assert!(resolve_alias("setgid").is_ok());
+        assert!(resolve_alias("sticky").is_ok());
+        assert!(resolve_alias("Readable").is_ok());
+        assert!(resolve_alias("WRITABLE").is_ok());
+        assert!(resolve_alias("orphaned").is_ok());
+        assert!(resolve_alias("hardlinked").is_ok());
+        assert!(resolve_alias("sparse").is_ok());
+        assert!(resolve_alias("mountpoint").is_ok());
+        assert!(resolve_alias("empty").is_ok());
+        assert!(resolve_alias("immutable").is_ok());
+        assert!(resolve_alias("append_only").is_ok());
+
+        let suggestions = suggest_aliases("exectuable");
+        assert!(suggestions.contains(&"executable".to_string()));
+    }
+
+    #[test]
+    fn test_xattr_existence() {
+        assert_eq!(
+            resolve_alias("xattr:security.capability").unwrap(),
+            Predicate::Metadata(Arc::new(MetadataPredicate::Xattr(XattrPredicate::Exists {
+                name: "security.capability".to_string()
+            })))
+        );
+        assert!(matches!(resolve_alias("xattr:"), Err(AliasError::Xattr(_))));
+    }
+
+    #[test]
+    fn test_content_aliases() {
+        assert_eq!(
+            resolve_alias("binary").unwrap(),
+            Predicate::contents(HeaderPredicate::Binary)
+        );
+        assert_eq!(
+            resolve_alias("Text").unwrap(),
+            Predicate::contents(HeaderPredicate::Text)
+        );
+
+        let suggestions = suggest_aliases("binry");
+        assert!(suggestions.contains(&"binary".to_string()));
+    }
+
     #[test]
     fn test_unknown_alias() {
         let result = resolve_alias("unknown");
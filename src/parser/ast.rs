@@ -17,6 +17,31 @@ pub enum RawValue<'a> {
                      // Typechecker interprets based on operator context
 }
 
+/// Function-style predicate like `has_child(name == tests)` or `head(20) contains x`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawCall<'a> {
+    pub name: &'a str,
+    pub args: Vec<RawArg<'a>>,
+    /// Optional trailing comparison applied to the call's result
+    pub comparison: Option<RawComparison<'a>>,
+    pub span: pest::Span<'a>,
+    pub name_span: pest::Span<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RawArg<'a> {
+    Expr(RawExpr<'a>, pest::Span<'a>), // Anything that parses as an expression
+    Value(RawValue<'a>, pest::Span<'a>), // Quoted strings and other bare arguments
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawComparison<'a> {
+    pub operator: &'a str,
+    pub value: RawValue<'a>,
+    pub operator_span: pest::Span<'a>,
+    pub value_span: pest::Span<'a>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum RawExpr<'a> {
     Not(Box<RawExpr<'a>>),
@@ -24,6 +49,7 @@ pub enum RawExpr<'a> {
     Or(Box<RawExpr<'a>>, Box<RawExpr<'a>>),
     Predicate(RawPredicate<'a>),
     SingleWord(pest::Span<'a>),
+    Call(RawCall<'a>),
 }
 
 impl<'a> RawExpr<'a> {
@@ -43,6 +69,37 @@ impl<'a> RawExpr<'a> {
                 test_utils::RawTestExpr::Predicate(pred.to_test_predicate())
             }
             RawExpr::SingleWord(span) => test_utils::RawTestExpr::SingleWord(span.as_str()),
+            RawExpr::Call(call) => test_utils::RawTestExpr::Call(call.to_test_call()),
+        }
+    }
+}
+
+impl<'a> RawCall<'a> {
+    /// Convert to test-friendly call without spans
+    pub fn to_test_call(&self) -> test_utils::RawTestCall<'a> {
+        test_utils::RawTestCall {
+            name: self.name,
+            args: self.args.iter().map(RawArg::to_test_arg).collect(),
+            comparison: self
+                .comparison
+                .as_ref()
+                .map(|c| (c.operator, c.value.to_test_value())),
+        }
+    }
+}
+
+impl<'a> RawArg<'a> {
+    pub fn span(&self) -> pest::Span<'a> {
+        match self {
+            RawArg::Expr(_, span) | RawArg::Value(_, span) => *span,
+        }
+    }
+
+    /// Convert to test-friendly argument without spans
+    pub fn to_test_arg(&self) -> test_utils::RawTestArg<'a> {
+        match self {
+            RawArg::Expr(expr, _) => test_utils::RawTestArg::Expr(expr.to_test_expr()),
+            RawArg::Value(value, _) => test_utils::RawTestArg::Value(value.to_test_value()),
         }
     }
 }
@@ -109,6 +166,20 @@ pub mod test_utils {
         Or(Box<RawTestExpr<'a>>, Box<RawTestExpr<'a>>),
         Predicate(RawTestPredicate<'a>),
         SingleWord(&'a str),
+        Call(RawTestCall<'a>),
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RawTestCall<'a> {
+        pub name: &'a str,
+        pub args: Vec<RawTestArg<'a>>,
+        pub comparison: Option<(&'a str, RawTestValue<'a>)>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum RawTestArg<'a> {
+        Expr(RawTestExpr<'a>),
+        Value(RawTestValue<'a>),
     }
 
     impl<'a> RawTestExpr<'a> {
@@ -141,6 +212,14 @@ pub mod test_utils {
             RawTestExpr::SingleWord(word)
         }
 
+        pub fn call(name: &'a str, args: Vec<RawTestArg<'a>>) -> Self {
+            RawTestExpr::Call(RawTestCall {
+                name,
+                args,
+                comparison: None,
+            })
+        }
+
         pub fn and(left: RawTestExpr<'a>, right: RawTestExpr<'a>) -> Self {
             RawTestExpr::And(Box::new(left), Box::new(right))
         }
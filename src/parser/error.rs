@@ -25,7 +25,7 @@ pub enum DetectError {
 
     // Typechecker errors with spans
     #[error("Unknown selector: {selector}")]
-    #[diagnostic(code(detect::unknown_selector), help("Valid selectors: name, basename, ext, path, dir, size, type, depth, modified, created, accessed, content"))]
+    #[diagnostic(code(detect::unknown_selector), help("Valid selectors: name, basename, ext, path, dir, size, disk_usage, blocks, type, fstype, depth, entries, tree_size, tree_files, tree_newest_mtime, mode, perm, selinux, user, group, uid, gid, inode, nlink, device, modified, created, accessed, content, line, all_lines, sha256, blake3, mime, lang, ftype, shebang, interpreter, encoding, eol, lines, lines[N..M], max_line_length, avg_line_length"))]
     UnknownSelector {
         selector: String,
         #[label("unknown selector")]
@@ -49,6 +49,33 @@ pub enum DetectError {
         src: String,
     },
 
+    #[error("Invalid line range: lines[{range}]")]
+    #[diagnostic(
+        code(detect::invalid_line_range),
+        help("Use lines[N] or lines[N..M], numbered from 1, with N <= M")
+    )]
+    InvalidLineRange {
+        range: String,
+        #[label("{reason}")]
+        span: SourceSpan,
+        reason: String,
+        #[source_code]
+        src: String,
+    },
+
+    #[error("Invalid extended attribute selector")]
+    #[diagnostic(
+        code(detect::invalid_xattr_name),
+        help("Extended attributes are selected by name, like xattr:user.origin")
+    )]
+    InvalidXattrName {
+        #[label("{reason}")]
+        span: SourceSpan,
+        reason: String,
+        #[source_code]
+        src: String,
+    },
+
     #[error("Unknown structured data format: '{format}'")]
     #[diagnostic(code(detect::unknown_structured_format))]
     UnknownStructuredFormat {
@@ -102,6 +129,31 @@ pub enum DetectError {
         src: String,
     },
 
+    #[error("Unknown function: {name}")]
+    #[diagnostic(
+        code(detect::unknown_function),
+        help("Valid functions: has_child, has_descendant, ancestor, sibling, content_eq, count, head, tail, near")
+    )]
+    UnknownFunction {
+        name: String,
+        #[label("unknown function")]
+        span: SourceSpan,
+        #[source_code]
+        src: String,
+    },
+
+    #[error("Invalid arguments to {function}: {reason}")]
+    #[diagnostic(code(detect::invalid_arguments), help("Usage: {usage}"))]
+    InvalidArguments {
+        function: String,
+        reason: String,
+        usage: String,
+        #[label("{reason}")]
+        span: SourceSpan,
+        #[source_code]
+        src: String,
+    },
+
     #[error("Expected {expected} value, found: {found}")]
     #[diagnostic(
         code(detect::invalid_value),
@@ -232,6 +284,17 @@ fn rule_to_friendly_name(rule: &Rule) -> &'static str {
         Rule::unterminated_string => "unterminated string",
         Rule::trailing_quote => "trailing quote",
         Rule::single_word => "single-word alias",
+        Rule::call => "function call",
+        Rule::call_name => "function name",
+        Rule::call_args => "function arguments",
+        Rule::call_arg => "function argument",
+        Rule::raw_arg => "function argument",
+        Rule::counted => "counted predicate",
+        Rule::count_keyword => "count",
+        Rule::proximity => "proximity predicate",
+        Rule::within_keyword => "within",
+        Rule::distance => "distance in lines",
+        Rule::of_keyword => "of",
         Rule::set_contents => "set contents",
         Rule::set_items => "set items",
         Rule::set_item => "set item",
@@ -359,9 +422,13 @@ impl DetectError {
             | DetectError::UnknownSelector { src: s, .. }
             | DetectError::InvalidStructuredPath { src: s, .. }
             | DetectError::UnknownStructuredFormat { src: s, .. }
+            | DetectError::InvalidXattrName { src: s, .. }
+            | DetectError::InvalidLineRange { src: s, .. }
             | DetectError::UnknownOperator { src: s, .. }
             | DetectError::UnknownAlias { src: s, .. }
             | DetectError::IncompatibleOperator { src: s, .. }
+            | DetectError::UnknownFunction { src: s, .. }
+            | DetectError::InvalidArguments { src: s, .. }
             | DetectError::InvalidValue { src: s, .. }
             | DetectError::InvalidEscape { src: s, .. }
             | DetectError::UnterminatedEscape { src: s, .. }
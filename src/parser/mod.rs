@@ -8,8 +8,8 @@ pub mod typechecker;
 pub mod typed;
 
 // Re-exports
-pub use aliases::{resolve_alias, suggest_aliases};
-pub use ast::{test_utils, RawExpr, RawPredicate, RawValue};
+pub use aliases::{all_aliases, resolve_alias, suggest_aliases};
+pub use ast::{test_utils, RawArg, RawCall, RawComparison, RawExpr, RawPredicate, RawValue};
 pub use error::DetectError;
 pub use raw::RawParser;
 pub use structured_path::{parse_path, PathComponent, PathParseError};
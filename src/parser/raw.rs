@@ -6,7 +6,7 @@ use pest::{
 use pest_derive::Parser;
 
 use super::{
-    ast::{RawExpr, RawPredicate, RawValue},
+    ast::{RawArg, RawCall, RawComparison, RawExpr, RawPredicate, RawValue},
     error::{DetectError, SpanExt},
 };
 
@@ -92,6 +92,9 @@ impl RawParser {
 
     fn parse_primary(pair: Pair<'_, Rule>) -> Result<RawExpr<'_>, DetectError> {
         match pair.as_rule() {
+            Rule::call => Self::parse_call(pair),
+            Rule::counted => Self::parse_counted(pair),
+            Rule::proximity => Self::parse_proximity(pair),
             Rule::predicate => Self::parse_predicate(pair),
             Rule::single_word => Ok(RawExpr::SingleWord(pair.as_span())),
             Rule::expr => Self::parse_expr(pair),
@@ -160,6 +163,153 @@ impl RawParser {
         }))
     }
 
+    fn parse_call(pair: Pair<'_, Rule>) -> Result<RawExpr<'_>, DetectError> {
+        let span = pair.as_span();
+        let mut inner = pair.into_inner();
+
+        let name_pair = inner
+            .next()
+            .ok_or_else(|| DetectError::internal("Grammar guarantees call has name"))?;
+        let name = name_pair.as_str();
+        let name_span = name_pair.as_span();
+
+        let mut args = Vec::new();
+        let mut comparison = None;
+        while let Some(next) = inner.next() {
+            match next.as_rule() {
+                Rule::call_arg => args.push(Self::parse_call_arg(next)?),
+                Rule::operator => {
+                    let value_pair = inner.next().ok_or_else(|| {
+                        DetectError::internal("Grammar guarantees call operator has value")
+                    })?;
+                    let value_span = value_pair.as_span();
+                    comparison = Some(RawComparison {
+                        operator: next.as_str(),
+                        operator_span: next.as_span(),
+                        value: Self::parse_value(value_pair)?,
+                        value_span,
+                    });
+                }
+                rule => {
+                    return Err(DetectError::internal(format!(
+                        "Unexpected call rule: {rule:?}"
+                    )))
+                }
+            }
+        }
+
+        Ok(RawExpr::Call(RawCall {
+            name,
+            args,
+            comparison,
+            span,
+            name_span,
+        }))
+    }
+
+    /// Parse `content contains TODO count > 5` as the call
+    /// `count(content contains TODO) > 5`
+    fn parse_counted(pair: Pair<'_, Rule>) -> Result<RawExpr<'_>, DetectError> {
+        let span = pair.as_span();
+        let mut inner = pair.into_inner();
+        let mut next = || {
+            inner
+                .next()
+                .ok_or_else(|| DetectError::internal("Grammar guarantees counted has all parts"))
+        };
+
+        let predicate_pair = next()?;
+        let predicate_span = predicate_pair.as_span();
+        let predicate = Self::parse_predicate(predicate_pair)?;
+        let name_pair = next()?;
+        let operator_pair = next()?;
+        let value_pair = next()?;
+        let value_span = value_pair.as_span();
+
+        Ok(RawExpr::Call(RawCall {
+            name: name_pair.as_str(),
+            args: vec![RawArg::Expr(predicate, predicate_span)],
+            comparison: Some(RawComparison {
+                operator: operator_pair.as_str(),
+                operator_span: operator_pair.as_span(),
+                value: Self::parse_value(value_pair)?,
+                value_span,
+            }),
+            span,
+            name_span: name_pair.as_span(),
+        }))
+    }
+
+    /// Parse `content contains lock within 5 lines of await` as the call
+    /// `near(content contains lock, content contains await, 5 lines)`
+    fn parse_proximity(pair: Pair<'_, Rule>) -> Result<RawExpr<'_>, DetectError> {
+        let span = pair.as_span();
+        let mut inner = pair.into_inner();
+        let mut next = || {
+            inner
+                .next()
+                .ok_or_else(|| DetectError::internal("Grammar guarantees proximity has all parts"))
+        };
+
+        let predicate_pair = next()?;
+        let predicate_span = predicate_pair.as_span();
+        let RawExpr::Predicate(first) = Self::parse_predicate(predicate_pair)? else {
+            return Err(DetectError::internal("parse_predicate returns a predicate"));
+        };
+        let keyword_pair = next()?;
+        let distance_pair = next()?;
+        let _of = next()?;
+        let value_pair = next()?;
+        let value_span = value_pair.as_span();
+
+        // The second pattern takes the selector and operator of the first
+        let second = RawPredicate {
+            value: Self::parse_value(value_pair)?,
+            span: value_span,
+            value_span,
+            ..first.clone()
+        };
+
+        Ok(RawExpr::Call(RawCall {
+            name: "near",
+            args: vec![
+                RawArg::Expr(RawExpr::Predicate(first), predicate_span),
+                RawArg::Expr(RawExpr::Predicate(second), value_span),
+                RawArg::Value(
+                    RawValue::Raw(distance_pair.as_str()),
+                    distance_pair.as_span(),
+                ),
+            ],
+            comparison: None,
+            span,
+            name_span: keyword_pair.as_span(),
+        }))
+    }
+
+    fn parse_call_arg(pair: Pair<'_, Rule>) -> Result<RawArg<'_>, DetectError> {
+        let span = pair.as_span();
+        let inner = pair
+            .into_inner()
+            .next()
+            .ok_or_else(|| DetectError::internal("Grammar guarantees call_arg has content"))?;
+
+        match inner.as_rule() {
+            Rule::expr => Ok(RawArg::Expr(Self::parse_expr(inner)?, span)),
+            Rule::quoted_string => Ok(RawArg::Value(Self::parse_value(inner)?, span)),
+            Rule::raw_arg => {
+                // Raw arguments run up to the next comma, so trailing whitespace is included
+                let text = inner.as_str().trim_end();
+                let span =
+                    pest::Span::new(span.get_input(), span.start(), span.start() + text.len())
+                        .unwrap_or(span);
+                Ok(RawArg::Value(RawValue::Raw(text), span))
+            }
+            rule => Err(DetectError::internal(format!(
+                "Unexpected call argument rule: {rule:?}"
+            ))),
+        }
+    }
+
     fn parse_value(pair: Pair<'_, Rule>) -> Result<RawValue<'_>, DetectError> {
         match pair.as_rule() {
             Rule::value => {
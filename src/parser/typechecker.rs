@@ -7,13 +7,17 @@ use crate::{
             self, EnumOperator, EnumSelector, NumericOperator, NumericSelector, PathComponent,
             StringOperator, StringSelector, TemporalOperator, TemporalSelector, TypedSelector,
         },
-        RawExpr, RawPredicate, RawValue,
+        RawArg, RawCall, RawExpr, RawPredicate, RawValue,
     },
     predicate::{
-        parse_time_value, Bound, DetectFileType, EnumMatcher, EnumPredicate, MetadataPredicate,
-        NamePredicate, NumberMatcher, Predicate, StreamingCompiledContentPredicate, StringMatcher,
-        TimeMatcher,
+        hash_file, parse_time_value, Bound, ContentPredicate, CountPredicate, DetectFileType,
+        Digest, EnumMatcher, EnumPredicate, FileTypeDefs, FileTypeMatcher, FsType, HashAlgorithm,
+        HashPredicate, HeaderPredicate, IdMatcher, Language, LineCheck, LineEnding, LineWindow,
+        LinesPredicate, MetadataPredicate, NamePredicate, NearPredicate, NumberMatcher,
+        PatternScope, Predicate, StreamingCompiledContentPredicate, StringMatcher, TextEncoding,
+        TimeMatcher, TreePredicate, TreeRelation, WindowPredicate, XattrPredicate, SELINUX_XATTR,
     },
+    BinaryPolicy,
 };
 
 /// Parse size values like "1mb", "100kb", etc. into bytes
@@ -60,6 +64,7 @@ impl Typechecker {
     ) -> Result<Expr<Predicate>, DetectError> {
         match raw_expr {
             RawExpr::Predicate(pred) => Self::typecheck_predicate(pred, source, config),
+            RawExpr::Call(call) => Self::typecheck_call(call, source, config),
             RawExpr::And(lhs, rhs) => {
                 let typed_lhs = Self::typecheck_inner(*lhs, source, config)?;
                 let typed_rhs = Self::typecheck_inner(*rhs, source, config)?;
@@ -79,6 +84,26 @@ impl Typechecker {
 
                 // Try to resolve as alias
                 match crate::parser::resolve_alias(word) {
+                    // Every file is text
+                    Ok(Predicate::Content(ContentPredicate::Header(HeaderPredicate::Text)))
+                        if config.binary == BinaryPolicy::Text =>
+                    {
+                        Ok(Expr::Predicate(Predicate::meta(MetadataPredicate::Type(
+                            EnumMatcher::Equals(DetectFileType::File),
+                        ))))
+                    }
+                    Ok(Predicate::Content(ContentPredicate::Header(HeaderPredicate::Binary)))
+                        if config.binary == BinaryPolicy::Text =>
+                    {
+                        Ok(Expr::Literal(false))
+                    }
+                    // Lines are read in the configured encoding, as for content patterns
+                    Ok(Predicate::Content(ContentPredicate::Lines(lines))) => {
+                        Ok(Self::skip_binary(
+                            Predicate::contents(lines.with_encoding(config.encoding)),
+                            config,
+                        ))
+                    }
                     Ok(predicate) => Ok(Expr::Predicate(predicate)),
                     Err(typed::AliasError::UnknownAlias(_)) => {
                         // Generate suggestions for file type aliases
@@ -86,7 +111,7 @@ impl Typechecker {
                         let suggestions_msg = if suggestions.is_empty() {
                             Some(format!(
                                 "Valid aliases: {}",
-                                crate::predicate::DetectFileType::all_valid_strings().join(", ")
+                                crate::parser::all_aliases().join(", ")
                             ))
                         } else {
                             Some(format!("Did you mean: {}?", suggestions.join(", ")))
@@ -120,11 +145,481 @@ impl Typechecker {
                         reason,
                         src: source.to_string(),
                     }),
+                    Err(typed::AliasError::Xattr(typed::XattrSelectorError { reason })) => {
+                        Err(DetectError::InvalidXattrName {
+                            span: span.to_source_span(),
+                            reason,
+                            src: source.to_string(),
+                        })
+                    }
                 }
             }
         }
     }
 
+    /// Transform a function-style predicate like `has_child(name == tests)`
+    fn typecheck_call(
+        call: RawCall<'_>,
+        source: &str,
+        config: &crate::RuntimeConfig,
+    ) -> Result<Expr<Predicate>, DetectError> {
+        let name = call.name.to_lowercase();
+        let usage = match name.as_str() {
+            "content_eq" => return Self::typecheck_content_eq(call, source),
+            "count" => return Self::typecheck_count(call, source, config),
+            "head" | "tail" => return Self::typecheck_window(call, source, config),
+            "near" => return Self::typecheck_near(call, source, config),
+            "has_child" => "has_child(expr)",
+            "has_descendant" => "has_descendant(expr) or has_descendant(expr, depth<=N)",
+            "ancestor" => "ancestor(expr)",
+            "sibling" => "sibling(expr)",
+            _ => {
+                return Err(DetectError::UnknownFunction {
+                    name: call.name.to_string(),
+                    span: call.name_span.to_source_span(),
+                    src: source.to_string(),
+                })
+            }
+        };
+        let invalid = |reason: &str, span: pest::Span| DetectError::InvalidArguments {
+            function: name.clone(),
+            reason: reason.to_string(),
+            usage: usage.to_string(),
+            span: span.to_source_span(),
+            src: source.to_string(),
+        };
+
+        if let Some(comparison) = &call.comparison {
+            return Err(invalid(
+                "doesn't take a comparison",
+                comparison.operator_span,
+            ));
+        }
+
+        let max_args = if name == "has_descendant" { 2 } else { 1 };
+        if call.args.is_empty() || call.args.len() > max_args {
+            return Err(invalid(
+                &format!("expected {usage}, found {} arguments", call.args.len()),
+                call.span,
+            ));
+        }
+
+        let mut args = call.args.into_iter();
+        let inner = match args.next() {
+            Some(RawArg::Expr(expr, span)) => {
+                let inner = Self::typecheck_inner(expr, source, config)?;
+                // Aggregates come from the main walk, which sub-expressions don't take part in
+                if inner.metadata_predicates().iter().any(|m| m.is_aggregate()) {
+                    return Err(invalid(
+                        "tree_size, tree_files and tree_newest_mtime can't be used here",
+                        span,
+                    ));
+                }
+                inner
+            }
+            Some(arg) => return Err(invalid("expected an expression", arg.span())),
+            None => unreachable!("argument count checked above"),
+        };
+
+        let relation = match (name.as_str(), args.next()) {
+            ("has_child", _) => TreeRelation::Child,
+            ("ancestor", _) => TreeRelation::Ancestor,
+            ("sibling", _) => TreeRelation::Sibling,
+            (_, None) => TreeRelation::Descendant(None),
+            (_, Some(arg)) => {
+                let span = arg.span();
+                TreeRelation::Descendant(Some(
+                    Self::parse_depth_limit(&arg)
+                        .ok_or_else(|| invalid("expected a depth limit like depth<=3", span))?,
+                ))
+            }
+        };
+
+        Ok(Expr::Predicate(Predicate::meta(MetadataPredicate::Tree(
+            TreePredicate::new(relation, inner),
+        ))))
+    }
+
+    /// Transform `content_eq("golden/file.txt")` into a size and blake3 check
+    /// against the reference file, which is read once at typecheck time
+    ///
+    /// Relative paths resolve against the current working directory, like the
+    /// reference files of `mtime_of(...)`.
+    fn typecheck_content_eq(
+        call: RawCall<'_>,
+        source: &str,
+    ) -> Result<Expr<Predicate>, DetectError> {
+        let invalid = |reason: String, span: pest::Span| DetectError::InvalidArguments {
+            function: "content_eq".to_string(),
+            reason,
+            usage: r#"content_eq("path/to/file")"#.to_string(),
+            span: span.to_source_span(),
+            src: source.to_string(),
+        };
+
+        if let Some(comparison) = &call.comparison {
+            return Err(invalid(
+                "doesn't take a comparison".to_string(),
+                comparison.operator_span,
+            ));
+        }
+
+        let (path, span) = match call.args.as_slice() {
+            [RawArg::Value(RawValue::Quoted(path) | RawValue::Raw(path), span)] => (*path, *span),
+            // Unquoted paths without operators parse as a single word
+            [RawArg::Expr(RawExpr::SingleWord(word), span)] => (word.as_str(), *span),
+            [arg] => return Err(invalid("expected a file path".to_string(), arg.span())),
+            args => {
+                return Err(invalid(
+                    format!("expected one file path, found {} arguments", args.len()),
+                    call.span,
+                ))
+            }
+        };
+
+        let metadata =
+            std::fs::metadata(path).map_err(|e| invalid(format!("{path}: {e}"), span))?;
+        let digest = hash_file(HashAlgorithm::Blake3, std::path::Path::new(path))
+            .map_err(|e| invalid(format!("{path}: {e}"), span))?;
+
+        // Only files of the same size are hashed at all
+        Ok(Expr::and(
+            Expr::Predicate(Predicate::meta(MetadataPredicate::Filesize(
+                NumberMatcher::Equals(metadata.len()),
+            ))),
+            Expr::Predicate(Predicate::contents(HashPredicate {
+                algorithm: HashAlgorithm::Blake3,
+                digests: vec![digest],
+            })),
+        ))
+    }
+
+    /// Transform `count(content contains TODO) > 5`, or the equivalent
+    /// `content contains TODO count > 5`, into a count of the pattern's
+    /// non-overlapping matches
+    fn typecheck_count(
+        call: RawCall<'_>,
+        source: &str,
+        config: &crate::RuntimeConfig,
+    ) -> Result<Expr<Predicate>, DetectError> {
+        let invalid = |reason: String, span: pest::Span| DetectError::InvalidArguments {
+            function: "count".to_string(),
+            reason,
+            usage: "count(content contains TODO) > 5".to_string(),
+            span: span.to_source_span(),
+            src: source.to_string(),
+        };
+
+        let Some(comparison) = call.comparison else {
+            return Err(invalid(
+                "expected a comparison, like > 5".to_string(),
+                call.span,
+            ));
+        };
+        let (pred, span) = match <[RawArg; 1]>::try_from(call.args) {
+            Ok([RawArg::Expr(RawExpr::Predicate(pred), span)]) => (pred, span),
+            Ok([arg]) => {
+                return Err(invalid(
+                    "expected a content predicate".to_string(),
+                    arg.span(),
+                ))
+            }
+            Err(args) => {
+                return Err(invalid(
+                    format!(
+                        "expected one content predicate, found {} arguments",
+                        args.len()
+                    ),
+                    call.span,
+                ))
+            }
+        };
+
+        let typed_selector = typed::parse_selector_operator(
+            pred.selector,
+            pred.selector_span,
+            pred.operator,
+            pred.operator_span,
+            source,
+        )?;
+        let pattern = match typed_selector {
+            TypedSelector::String(selector @ StringSelector::Contents, operator) => {
+                match Self::build_string_predicate(
+                    selector,
+                    operator,
+                    &pred.value,
+                    pred.value_span,
+                    source,
+                    config,
+                )? {
+                    Predicate::Content(ContentPredicate::Regex(pattern)) => pattern,
+                    _ => unreachable!("content selectors build content patterns"),
+                }
+            }
+            _ => {
+                return Err(invalid(
+                    "only content patterns can be counted".to_string(),
+                    span,
+                ))
+            }
+        };
+        if pattern.can_match_empty() {
+            return Err(invalid(
+                "can't count a pattern that matches empty text".to_string(),
+                pred.value_span,
+            ));
+        }
+
+        let operator = match typed::parse_numeric_operator(comparison.operator) {
+            Ok(NumericOperator::AnyBits) | Err(_) => {
+                return Err(invalid(
+                    format!(
+                        "expected a numeric comparison, found {}",
+                        comparison.operator
+                    ),
+                    comparison.operator_span,
+                ))
+            }
+            Ok(operator) => operator,
+        };
+        let count =
+            comparison
+                .value
+                .as_string()
+                .parse()
+                .map_err(|_| DetectError::InvalidValue {
+                    expected: "match count".to_string(),
+                    found: comparison.value.as_string().to_string(),
+                    span: comparison.value_span.to_source_span(),
+                    src: source.to_string(),
+                })?;
+
+        let predicate = Predicate::contents(CountPredicate {
+            pattern,
+            matcher: Self::build_number_matcher(operator, count),
+        });
+        Ok(Self::skip_binary(predicate, config))
+    }
+
+    /// Under `--binary skip`, rule out binary files from their first block,
+    /// before a content pattern scans the whole file
+    fn skip_binary(predicate: Predicate, config: &crate::RuntimeConfig) -> Expr<Predicate> {
+        if config.binary == BinaryPolicy::Skip {
+            Expr::and(
+                Expr::Predicate(Predicate::contents(HeaderPredicate::Text)),
+                Expr::Predicate(predicate),
+            )
+        } else {
+            Expr::Predicate(predicate)
+        }
+    }
+
+    /// `head(20) contains Copyright`, `tail(5) ~= "^}$"`: a content pattern
+    /// matching any single line among the first or last lines of a file
+    fn typecheck_window(
+        call: RawCall<'_>,
+        source: &str,
+        config: &crate::RuntimeConfig,
+    ) -> Result<Expr<Predicate>, DetectError> {
+        let name = call.name.to_lowercase();
+        let usage = if name == "head" {
+            "head(20) contains Copyright"
+        } else {
+            "tail(5) ~= \"^}$\""
+        };
+        let invalid = |reason: String, span: pest::Span| DetectError::InvalidArguments {
+            function: name.clone(),
+            reason,
+            usage: usage.to_string(),
+            span: span.to_source_span(),
+            src: source.to_string(),
+        };
+
+        let Some(comparison) = call.comparison else {
+            return Err(invalid(
+                "expected a comparison, like contains TODO".to_string(),
+                call.span,
+            ));
+        };
+        let n = match <[RawArg; 1]>::try_from(call.args) {
+            Ok([RawArg::Value(value, span)]) => match value.as_string().parse::<u64>() {
+                Ok(n) if n > 0 => n,
+                _ => {
+                    return Err(invalid(
+                        format!("expected a number of lines, found {}", value.as_string()),
+                        span,
+                    ))
+                }
+            },
+            Ok([arg]) => {
+                return Err(invalid(
+                    "expected a number of lines".to_string(),
+                    arg.span(),
+                ))
+            }
+            Err(args) => {
+                return Err(invalid(
+                    format!("expected 1 argument, found {}", args.len()),
+                    call.span,
+                ))
+            }
+        };
+
+        let operator = match typed::parse_string_operator(comparison.operator) {
+            Ok(StringOperator::In | StringOperator::NotEquals) | Err(_) => {
+                return Err(invalid(
+                    format!("expected ==, contains or ~=, found {}", comparison.operator),
+                    comparison.operator_span,
+                ))
+            }
+            Ok(operator) => operator,
+        };
+        let pattern = Self::compile_content_pattern(
+            &comparison.value,
+            operator,
+            comparison.value_span,
+            source,
+            config,
+        )?;
+
+        let window = if name == "head" {
+            LineWindow::Head(n)
+        } else {
+            LineWindow::Tail(n)
+        };
+        let predicate = Predicate::contents(WindowPredicate {
+            pattern: pattern.with_scope(PatternScope::AnyLine),
+            window,
+        });
+        Ok(Self::skip_binary(predicate, config))
+    }
+
+    /// `near("unsafe", "// SAFETY:", 3 lines)`, or desugared from
+    /// `content contains lock within 5 lines of await`: lines matching each
+    /// pattern at most N lines apart
+    fn typecheck_near(
+        call: RawCall<'_>,
+        source: &str,
+        config: &crate::RuntimeConfig,
+    ) -> Result<Expr<Predicate>, DetectError> {
+        let invalid = |reason: String, span: pest::Span| DetectError::InvalidArguments {
+            function: "near".to_string(),
+            reason,
+            usage: "near(\"unsafe\", \"// SAFETY:\", 3 lines)".to_string(),
+            span: span.to_source_span(),
+            src: source.to_string(),
+        };
+
+        if let Some(comparison) = &call.comparison {
+            return Err(invalid(
+                "doesn't take a comparison".to_string(),
+                comparison.operator_span,
+            ));
+        }
+        let [first, second, distance] = match <[RawArg; 3]>::try_from(call.args) {
+            Ok(args) => args,
+            Err(args) => {
+                return Err(invalid(
+                    format!("expected 3 arguments, found {}", args.len()),
+                    call.span,
+                ))
+            }
+        };
+
+        let distance = match distance {
+            RawArg::Value(value, span) => {
+                let text = value.as_string();
+                let number = text
+                    .strip_suffix("lines")
+                    .or_else(|| text.strip_suffix("line"))
+                    .unwrap_or(text);
+                number.trim().parse::<u64>().map_err(|_| {
+                    invalid(
+                        format!("expected a distance like 3 lines, found {text}"),
+                        span,
+                    )
+                })?
+            }
+            RawArg::Expr(_, span) => {
+                return Err(invalid(
+                    "expected a distance like 3 lines".to_string(),
+                    span,
+                ))
+            }
+        };
+
+        let pattern = |arg: RawArg<'_>| match arg {
+            // A string is searched for as is, like `content contains`
+            RawArg::Value(value, span) => Self::compile_content_pattern(
+                &value,
+                StringOperator::Contains,
+                span,
+                source,
+                config,
+            ),
+            RawArg::Expr(RawExpr::SingleWord(word), span) => Self::compile_content_pattern(
+                &RawValue::Raw(word.as_str()),
+                StringOperator::Contains,
+                span,
+                source,
+                config,
+            ),
+            RawArg::Expr(RawExpr::Predicate(pred), span) => {
+                match typed::parse_selector_operator(
+                    pred.selector,
+                    pred.selector_span,
+                    pred.operator,
+                    pred.operator_span,
+                    source,
+                )? {
+                    TypedSelector::String(
+                        StringSelector::Contents | StringSelector::Line,
+                        operator,
+                    ) => Self::compile_content_pattern(
+                        &pred.value,
+                        operator,
+                        pred.value_span,
+                        source,
+                        config,
+                    ),
+                    _ => Err(invalid(
+                        "expected a string or a content pattern".to_string(),
+                        span,
+                    )),
+                }
+            }
+            RawArg::Expr(_, span) => Err(invalid(
+                "expected a string or a content pattern".to_string(),
+                span,
+            )),
+        };
+        let first = pattern(first)?;
+        let second = pattern(second)?;
+
+        let predicate = Predicate::contents(NearPredicate {
+            first: first.with_scope(PatternScope::AnyLine),
+            second: second.with_scope(PatternScope::AnyLine),
+            distance,
+        });
+        Ok(Self::skip_binary(predicate, config))
+    }
+
+    /// Parse `depth<=N` or `depth<N` into the maximum depth below a directory
+    fn parse_depth_limit(arg: &RawArg) -> Option<u32> {
+        let RawArg::Expr(RawExpr::Predicate(pred), _) = arg else {
+            return None;
+        };
+        if !pred.selector.eq_ignore_ascii_case("depth") {
+            return None;
+        }
+        let n: u32 = pred.value.as_string().parse().ok()?;
+        match pred.operator {
+            "<=" => Some(n),
+            "<" => n.checked_sub(1),
+            _ => None,
+        }
+    }
+
     /// Build synthetic precondition for structured data predicates
     /// Wraps actual predicate in: (ext in [exts]) AND (size < max) AND `actual_predicate`
     fn build_synthetic_precondition(
@@ -175,24 +670,41 @@ impl Typechecker {
 
         match typed_selector {
             TypedSelector::String(selector, operator) => {
+                let is_pattern = selector.is_content_pattern();
                 let predicate = Self::build_string_predicate(
                     selector,
                     operator,
                     &pred.value,
                     pred.value_span,
                     source,
+                    config,
                 )?;
-                Ok(Expr::Predicate(predicate))
+                if is_pattern {
+                    Ok(Self::skip_binary(predicate, config))
+                } else {
+                    Ok(Expr::Predicate(predicate))
+                }
             }
             TypedSelector::Numeric(selector, operator) => {
+                let reads_lines = matches!(
+                    selector,
+                    NumericSelector::Lines
+                        | NumericSelector::MaxLineLength
+                        | NumericSelector::AvgLineLength
+                );
                 let predicate = Self::build_numeric_predicate(
                     selector,
                     operator,
                     &pred.value,
                     pred.value_span,
                     source,
+                    config,
                 )?;
-                Ok(Expr::Predicate(predicate))
+                if reads_lines {
+                    Ok(Self::skip_binary(predicate, config))
+                } else {
+                    Ok(Expr::Predicate(predicate))
+                }
             }
             TypedSelector::Temporal(selector, operator) => {
                 let predicate = Self::build_temporal_predicate(
@@ -205,14 +717,20 @@ impl Typechecker {
                 Ok(Expr::Predicate(predicate))
             }
             TypedSelector::Enum(selector, operator) => {
+                let reads_lines = selector == EnumSelector::Eol;
                 let predicate = Self::build_enum_predicate(
                     selector,
                     operator,
                     &pred.value,
                     pred.value_span,
                     source,
+                    config,
                 )?;
-                Ok(Expr::Predicate(predicate))
+                if reads_lines {
+                    Ok(Self::skip_binary(predicate, config))
+                } else {
+                    Ok(Expr::Predicate(predicate))
+                }
             }
             TypedSelector::StructuredData(format, path, operator) => {
                 let predicate = Self::build_structured_predicate(
@@ -240,6 +758,13 @@ impl Typechecker {
                     format, config, predicate,
                 ))
             }
+            TypedSelector::Xattr(name, operator) => {
+                let matcher =
+                    Self::parse_string_value(&pred.value, operator, pred.value_span, source)?;
+                Ok(Expr::Predicate(Predicate::meta(MetadataPredicate::Xattr(
+                    XattrPredicate::Value { name, matcher },
+                ))))
+            }
         }
     }
 
@@ -250,6 +775,7 @@ impl Typechecker {
         value: &RawValue,
         value_span: pest::Span,
         source: &str,
+        config: &crate::RuntimeConfig,
     ) -> Result<Predicate, DetectError> {
         let string_matcher = Self::parse_string_value(value, operator, value_span, source)?;
 
@@ -264,18 +790,58 @@ impl Typechecker {
                 };
                 Ok(Predicate::name(name_pred))
             }
-            StringSelector::Contents => {
-                let pattern = Self::build_content_pattern(value, operator, value_span, source)?;
-                let content_pred =
-                    StreamingCompiledContentPredicate::new(pattern).map_err(|e| {
-                        DetectError::InvalidValue {
-                            expected: "valid regex pattern".to_string(),
-                            found: format!("{e:?}"),
+            StringSelector::Contents | StringSelector::Line | StringSelector::AllLines => {
+                let scope = match selector {
+                    StringSelector::Line => PatternScope::AnyLine,
+                    StringSelector::AllLines => PatternScope::AllLines,
+                    _ => PatternScope::File,
+                };
+                let pattern =
+                    Self::compile_content_pattern(value, operator, value_span, source, config)?;
+                Ok(Predicate::contents(pattern.with_scope(scope)))
+            }
+            StringSelector::LineRange(first, last) => {
+                let pattern =
+                    Self::compile_content_pattern(value, operator, value_span, source, config)?;
+                Ok(Predicate::contents(WindowPredicate {
+                    pattern: pattern.with_scope(PatternScope::AnyLine),
+                    window: LineWindow::Range(first, last),
+                }))
+            }
+            StringSelector::Mime => Ok(Predicate::contents(HeaderPredicate::Mime(string_matcher))),
+            StringSelector::Shebang => Ok(Predicate::contents(HeaderPredicate::Shebang(
+                string_matcher,
+            ))),
+            StringSelector::Interpreter => Ok(Predicate::contents(HeaderPredicate::Interpreter(
+                string_matcher,
+            ))),
+            StringSelector::Permissions => Ok(Predicate::meta(MetadataPredicate::Permissions(
+                string_matcher,
+            ))),
+            StringSelector::Selinux => Ok(Predicate::meta(MetadataPredicate::Xattr(
+                XattrPredicate::Value {
+                    name: SELINUX_XATTR.to_string(),
+                    matcher: string_matcher,
+                },
+            ))),
+            StringSelector::Hash(algorithm) => {
+                let values = match string_matcher {
+                    StringMatcher::Equals(value) => vec![value],
+                    StringMatcher::In(values) => values.into_iter().collect(),
+                    _ => unreachable!("hash selectors only accept == and in"),
+                };
+                let digests = values
+                    .iter()
+                    .map(|value| {
+                        Digest::from_hex(value).ok_or_else(|| DetectError::InvalidValue {
+                            expected: "64 character hex digest".to_string(),
+                            found: value.clone(),
                             span: value_span.to_source_span(),
                             src: source.to_string(),
-                        }
-                    })?;
-                Ok(Predicate::contents(content_pred))
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(Predicate::contents(HashPredicate { algorithm, digests }))
             }
         }
     }
@@ -287,15 +853,48 @@ impl Typechecker {
         value: &RawValue,
         value_span: pest::Span,
         source: &str,
+        config: &crate::RuntimeConfig,
     ) -> Result<Predicate, DetectError> {
         let number_value = Self::parse_numeric_value(value, &selector, value_span, source)?;
         let number_matcher = Self::build_number_matcher(operator, number_value);
+        // Lines are counted in the configured encoding, as for content patterns
+        let lines = |check| {
+            Ok(Predicate::contents(
+                LinesPredicate::new(check).with_encoding(config.encoding),
+            ))
+        };
 
         match selector {
             NumericSelector::Size => {
                 Ok(Predicate::meta(MetadataPredicate::Filesize(number_matcher)))
             }
             NumericSelector::Depth => Ok(Predicate::name(NamePredicate::Depth(number_matcher))),
+            NumericSelector::Mode => Ok(Predicate::meta(MetadataPredicate::Mode(number_matcher))),
+            NumericSelector::Entries => {
+                Ok(Predicate::meta(MetadataPredicate::Entries(number_matcher)))
+            }
+            NumericSelector::DiskUsage => Ok(Predicate::meta(MetadataPredicate::DiskUsage(
+                number_matcher,
+            ))),
+            NumericSelector::Blocks => {
+                Ok(Predicate::meta(MetadataPredicate::Blocks(number_matcher)))
+            }
+            NumericSelector::Uid => Ok(Predicate::meta(MetadataPredicate::Uid(number_matcher))),
+            NumericSelector::Gid => Ok(Predicate::meta(MetadataPredicate::Gid(number_matcher))),
+            NumericSelector::Inode => Ok(Predicate::meta(MetadataPredicate::Inode(number_matcher))),
+            NumericSelector::Nlink => Ok(Predicate::meta(MetadataPredicate::Nlink(number_matcher))),
+            NumericSelector::Device => {
+                Ok(Predicate::meta(MetadataPredicate::Device(number_matcher)))
+            }
+            NumericSelector::TreeSize => {
+                Ok(Predicate::meta(MetadataPredicate::TreeSize(number_matcher)))
+            }
+            NumericSelector::TreeFiles => Ok(Predicate::meta(MetadataPredicate::TreeFiles(
+                number_matcher,
+            ))),
+            NumericSelector::Lines => lines(LineCheck::Lines(number_matcher)),
+            NumericSelector::MaxLineLength => lines(LineCheck::MaxLineLength(number_matcher)),
+            NumericSelector::AvgLineLength => lines(LineCheck::AvgLineLength(number_matcher)),
         }
     }
 
@@ -307,13 +906,33 @@ impl Typechecker {
         value_span: pest::Span,
         source: &str,
     ) -> Result<Predicate, DetectError> {
-        let time_value = Self::parse_temporal_value(value, value_span, source)?;
-        let time_matcher = Self::build_time_matcher(operator, time_value);
+        // Another timestamp of the same entity, e.g. `modified > created`.
+        // Tree aggregates only exist for the left-hand side of a directory.
+        let rhs_selector = match value {
+            RawValue::Raw(s) => match typed::recognize_selector(s) {
+                Ok(typed::SelectorCategory::Temporal(rhs))
+                    if rhs != TemporalSelector::TreeNewestMtime =>
+                {
+                    Some(rhs)
+                }
+                _ => None,
+            },
+            RawValue::Quoted(_) => None,
+        };
+
+        let time_matcher = match rhs_selector {
+            Some(rhs) => TimeMatcher::Selector(operator, rhs),
+            None => {
+                let time_value = Self::parse_temporal_value(value, value_span, source)?;
+                Self::build_time_matcher(operator, time_value)
+            }
+        };
 
         let meta_pred = match selector {
             TemporalSelector::Modified => MetadataPredicate::Modified(time_matcher),
             TemporalSelector::Created => MetadataPredicate::Created(time_matcher),
             TemporalSelector::Accessed => MetadataPredicate::Accessed(time_matcher),
+            TemporalSelector::TreeNewestMtime => MetadataPredicate::TreeNewestMtime(time_matcher),
         };
         Ok(Predicate::meta(meta_pred))
     }
@@ -325,6 +944,7 @@ impl Typechecker {
         value: &RawValue,
         value_span: pest::Span,
         source: &str,
+        config: &crate::RuntimeConfig,
     ) -> Result<Predicate, DetectError> {
         match selector {
             EnumSelector::Type => {
@@ -332,6 +952,49 @@ impl Typechecker {
                     Self::parse_enum_value::<DetectFileType>(value, operator, value_span, source)?;
                 Ok(Predicate::meta(MetadataPredicate::Type(enum_matcher)))
             }
+            EnumSelector::FsType => {
+                let enum_matcher =
+                    Self::parse_enum_value::<FsType>(value, operator, value_span, source)?;
+                Ok(Predicate::meta(MetadataPredicate::FsType(enum_matcher)))
+            }
+            EnumSelector::Lang => {
+                let enum_matcher =
+                    Self::parse_enum_value::<Language>(value, operator, value_span, source)?;
+                Ok(Predicate::contents(HeaderPredicate::Lang(enum_matcher)))
+            }
+            EnumSelector::Eol => {
+                let enum_matcher =
+                    Self::parse_enum_value::<LineEnding>(value, operator, value_span, source)?;
+                Ok(Predicate::contents(
+                    LinesPredicate::new(LineCheck::Eol(enum_matcher))
+                        .with_encoding(config.encoding),
+                ))
+            }
+            EnumSelector::Encoding => {
+                let enum_matcher =
+                    Self::parse_enum_value::<TextEncoding>(value, operator, value_span, source)?;
+                Ok(Predicate::contents(HeaderPredicate::Encoding(enum_matcher)))
+            }
+            EnumSelector::Ftype => {
+                let matcher = Self::parse_ftype_value(
+                    value,
+                    operator,
+                    value_span,
+                    source,
+                    &config.file_types,
+                )?;
+                Ok(Predicate::name(NamePredicate::FileType(matcher)))
+            }
+            EnumSelector::User => {
+                let id_matcher =
+                    Self::parse_id_value(selector, value, operator, value_span, source)?;
+                Ok(Predicate::meta(MetadataPredicate::User(id_matcher)))
+            }
+            EnumSelector::Group => {
+                let id_matcher =
+                    Self::parse_id_value(selector, value, operator, value_span, source)?;
+                Ok(Predicate::meta(MetadataPredicate::Group(id_matcher)))
+            }
         }
     }
 
@@ -572,6 +1235,26 @@ impl Typechecker {
         Ok(StringMatcher::In(set))
     }
 
+    /// Compile a content pattern, decoding files as `config` says
+    fn compile_content_pattern(
+        value: &RawValue,
+        operator: StringOperator,
+        value_span: pest::Span,
+        source: &str,
+        config: &crate::RuntimeConfig,
+    ) -> Result<StreamingCompiledContentPredicate, DetectError> {
+        let pattern = Self::build_content_pattern(value, operator, value_span, source)?;
+        let compiled = StreamingCompiledContentPredicate::new(pattern).map_err(|e| {
+            DetectError::InvalidValue {
+                expected: "valid regex pattern".to_string(),
+                found: format!("{e:?}"),
+                span: value_span.to_source_span(),
+                src: source.to_string(),
+            }
+        })?;
+        Ok(compiled.with_encoding(config.encoding))
+    }
+
     /// Build content pattern based on operator
     fn build_content_pattern(
         value: &RawValue,
@@ -609,8 +1292,21 @@ impl Typechecker {
             RawValue::Quoted(s) | RawValue::Raw(s) => s,
         };
 
-        if matches!(selector, NumericSelector::Size) && s.chars().any(char::is_alphabetic) {
+        if matches!(
+            selector,
+            NumericSelector::Size | NumericSelector::DiskUsage | NumericSelector::TreeSize
+        ) && s.chars().any(char::is_alphabetic)
+        {
             parse_size_value(s, value_span, source)
+        } else if matches!(selector, NumericSelector::Mode) {
+            // Modes are always octal, like chmod: 644, 0644, 0o644
+            let digits = s.strip_prefix("0o").unwrap_or(s);
+            u64::from_str_radix(digits, 8).map_err(|_| DetectError::InvalidValue {
+                expected: "octal permission bits (e.g., 0644, 0o755)".to_string(),
+                found: (*s).to_string(),
+                span: value_span.to_source_span(),
+                src: source.to_string(),
+            })
         } else {
             s.parse().map_err(|_| DetectError::InvalidValue {
                 expected: "numeric value".to_string(),
@@ -630,6 +1326,7 @@ impl Typechecker {
             NumericOperator::GreaterOrEqual => NumberMatcher::In(Bound::Left(value..)),
             NumericOperator::Less => NumberMatcher::In(Bound::Right(..value)),
             NumericOperator::LessOrEqual => NumberMatcher::In(Bound::Right(..(value + 1))),
+            NumericOperator::AnyBits => NumberMatcher::AnyBits(value),
         }
     }
 
@@ -643,6 +1340,10 @@ impl Typechecker {
             RawValue::Quoted(s) | RawValue::Raw(s) => s,
         };
 
+        if let Some((selector, path)) = Self::parse_reference_file(s) {
+            return Self::stat_reference_file(selector, path, value_span, source);
+        }
+
         parse_time_value(s).map_err(|e| DetectError::InvalidValue {
             expected: "valid time".to_string(),
             found: format!("{s}: {e:?}"),
@@ -651,6 +1352,48 @@ impl Typechecker {
         })
     }
 
+    /// Recognize reference file values like `mtime_of("target/.stamp")`
+    ///
+    /// Returns the timestamp to read and the (unquoted) reference path.
+    fn parse_reference_file(s: &str) -> Option<(TemporalSelector, &str)> {
+        let (func, rest) = s.split_once('(')?;
+        let arg = rest.strip_suffix(')')?.trim();
+
+        let selector = match func.trim() {
+            "mtime_of" | "modified_of" => TemporalSelector::Modified,
+            "ctime_of" | "created_of" => TemporalSelector::Created,
+            "atime_of" | "accessed_of" => TemporalSelector::Accessed,
+            _ => return None,
+        };
+
+        let path = arg
+            .strip_prefix('"')
+            .and_then(|a| a.strip_suffix('"'))
+            .or_else(|| arg.strip_prefix('\'').and_then(|a| a.strip_suffix('\'')))
+            .unwrap_or(arg);
+
+        Some((selector, path))
+    }
+
+    /// Stat a reference file once, at typecheck time, and read one of its timestamps
+    ///
+    /// Relative paths resolve against the current working directory, like `find -newer`.
+    fn stat_reference_file(
+        selector: TemporalSelector,
+        path: &str,
+        value_span: pest::Span,
+        source: &str,
+    ) -> Result<chrono::DateTime<chrono::Local>, DetectError> {
+        let metadata = std::fs::metadata(path).map_err(|e| DetectError::InvalidValue {
+            expected: "readable reference file".to_string(),
+            found: format!("{path}: {e}"),
+            span: value_span.to_source_span(),
+            src: source.to_string(),
+        })?;
+
+        Ok(crate::predicate::timestamp_of(selector, &metadata).into())
+    }
+
     /// Build time matcher from operator and value
     fn build_time_matcher(
         operator: TemporalOperator,
@@ -666,6 +1409,51 @@ impl Typechecker {
         }
     }
 
+    /// Resolve user or group names (or numeric ids) to ids at parse time
+    fn parse_id_value(
+        selector: EnumSelector,
+        value: &RawValue,
+        operator: EnumOperator,
+        value_span: pest::Span,
+        source: &str,
+    ) -> Result<IdMatcher, DetectError> {
+        let resolve = |name: &str| -> Result<u32, DetectError> {
+            let (id, expected) = match selector {
+                EnumSelector::Group => (crate::predicate::group_id(name), "a group name or gid"),
+                _ => (crate::predicate::user_id(name), "a user name or uid"),
+            };
+            name.parse::<u32>()
+                .ok()
+                .or(id)
+                .ok_or_else(|| DetectError::InvalidValue {
+                    expected: expected.to_string(),
+                    found: name.to_string(),
+                    span: value_span.to_source_span(),
+                    src: source.to_string(),
+                })
+        };
+
+        let value_str = match value {
+            RawValue::Quoted(s) | RawValue::Raw(s) => s,
+        };
+
+        match operator {
+            EnumOperator::Equals => Ok(IdMatcher::Equals(resolve(value_str)?)),
+            EnumOperator::NotEquals => Ok(IdMatcher::NotEquals(resolve(value_str)?)),
+            EnumOperator::In => {
+                let items = match Self::parse_as_set(value_str, value_span, source)? {
+                    StringMatcher::In(set) => set,
+                    _ => unreachable!("parse_as_set should return StringMatcher::In"),
+                };
+                let ids = items
+                    .iter()
+                    .map(|item| resolve(item))
+                    .collect::<Result<_, _>>()?;
+                Ok(IdMatcher::In(ids))
+            }
+        }
+    }
+
     /// Parse and validate enum values at parse time using the `EnumPredicate` trait
     fn parse_enum_value<E: EnumPredicate>(
         value: &RawValue,
@@ -725,4 +1513,46 @@ impl Typechecker {
             }
         }
     }
+
+    /// Parse file type names for `ftype`, checked against the defined types
+    fn parse_ftype_value(
+        value: &RawValue,
+        operator: EnumOperator,
+        value_span: pest::Span,
+        source: &str,
+        file_types: &FileTypeDefs,
+    ) -> Result<FileTypeMatcher, DetectError> {
+        let value_str = match value {
+            RawValue::Quoted(s) | RawValue::Raw(s) => s,
+        };
+
+        let names = match operator {
+            EnumOperator::Equals | EnumOperator::NotEquals => vec![(*value_str).to_string()],
+            EnumOperator::In => match Self::parse_as_set(value_str, value_span, source)? {
+                StringMatcher::In(set) => set.into_iter().collect(),
+                _ => unreachable!("parse_as_set should return StringMatcher::In"),
+            },
+        };
+
+        FileTypeMatcher::new(file_types, names, operator == EnumOperator::NotEquals).map_err(
+            |name| {
+                let suggestions =
+                    crate::parser::aliases::suggest_similar(&name, file_types.names());
+                let expected = if suggestions.is_empty() {
+                    "one of the defined file types".to_string()
+                } else {
+                    format!(
+                        "one of the defined file types (closest: {})",
+                        suggestions.join(", ")
+                    )
+                };
+                DetectError::InvalidValue {
+                    expected,
+                    found: name,
+                    span: value_span.to_source_span(),
+                    src: source.to_string(),
+                }
+            },
+        )
+    }
 }
@@ -20,6 +20,10 @@ pub enum ParseError {
     },
     /// Unknown structured data format (not yaml/json/toml)
     UnknownStructuredFormat { format: String },
+    /// Invalid extended attribute name
+    InvalidXattrName { reason: String },
+    /// Malformed or empty `lines[N..M]` range
+    InvalidLineRange { range: String, reason: String },
 }
 
 /// Error type for parsing structured selectors (yaml:, json:, toml:)
@@ -35,6 +39,12 @@ pub enum StructuredSelectorError {
     },
 }
 
+/// Error type for parsing extended attribute selectors (xattr:)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XattrSelectorError {
+    pub reason: String,
+}
+
 /// Error type for resolving aliases (single-word expressions)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AliasError {
@@ -42,6 +52,8 @@ pub enum AliasError {
     UnknownAlias(String),
     /// Structured selector error
     Structured(StructuredSelectorError),
+    /// Extended attribute selector error
+    Xattr(XattrSelectorError),
 }
 
 // ============================================================================
@@ -67,6 +79,7 @@ pub enum NumericOperator {
     GreaterOrEqual, // >=, =>, gte, ge
     Less,           // <, lt
     LessOrEqual,    // <=, =<, lte, le
+    AnyBits,        // & (mode only)
 }
 
 /// Operators that can be applied to temporal-type selectors
@@ -106,7 +119,23 @@ pub enum PathComponent {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StringSelector {
     Path(PathComponent),
-    Contents, // contents, content, text
+    Contents,            // contents, content, text
+    Line,                // line - any single line matches
+    AllLines,            // all_lines - every line matches
+    LineRange(u64, u64), // lines[1..3] - any line in the range matches, numbered from 1
+    Permissions,         // perm, perms, permissions - symbolic, e.g. rwxr-x---
+    Selinux,             // selinux - security context label
+    Hash(HashAlgorithm), // sha256, blake3 - hex digest of the contents
+    Mime,                // mime - MIME type from magic numbers
+    Shebang,             // shebang - first line after #!
+    Interpreter,         // interpreter - program named by the shebang
+}
+
+/// Content digest algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Blake3,
 }
 
 impl StringSelector {
@@ -119,29 +148,73 @@ impl StringSelector {
             StringSelector::Path(PathComponent::Extension) => "ext",
             StringSelector::Path(PathComponent::Parent) => "dir",
             StringSelector::Contents => "content",
+            StringSelector::Line => "line",
+            StringSelector::AllLines => "all_lines",
+            StringSelector::LineRange(..) => "lines[]",
+            StringSelector::Permissions => "perm",
+            StringSelector::Selinux => "selinux",
+            StringSelector::Hash(HashAlgorithm::Sha256) => "sha256",
+            StringSelector::Hash(HashAlgorithm::Blake3) => "blake3",
+            StringSelector::Mime => "mime",
+            StringSelector::Shebang => "shebang",
+            StringSelector::Interpreter => "interpreter",
         }
     }
+
+    /// Whether this selector takes a content pattern, which doesn't support
+    /// `in` or `!=`
+    pub fn is_content_pattern(&self) -> bool {
+        matches!(
+            self,
+            StringSelector::Contents
+                | StringSelector::Line
+                | StringSelector::AllLines
+                | StringSelector::LineRange(..)
+        )
+    }
 }
 
 /// Numeric-type selectors
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NumericSelector {
-    Size,  // size, filesize, bytes
-    Depth, // depth, level
+    Size,          // size, filesize, bytes
+    DiskUsage,     // disk_usage, du - allocated bytes
+    Blocks,        // blocks - allocated 512-byte blocks
+    Depth,         // depth, level
+    Entries,       // entries, children - directory entry count
+    Mode,          // mode - permission bits, octal
+    Uid,           // uid
+    Gid,           // gid
+    Inode,         // inode, ino
+    Nlink,         // nlink, links
+    Device,        // device, dev
+    TreeSize,      // tree_size - total file size below a directory
+    TreeFiles,     // tree_files - number of files below a directory
+    Lines,         // lines - line count, from the content read
+    MaxLineLength, // max_line_length - longest line, in characters
+    AvgLineLength, // avg_line_length - mean line length, rounded down
 }
 
 /// Temporal-type selectors
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TemporalSelector {
-    Modified, // modified, mtime, mod, modification
-    Created,  // created, ctime, birth, birthtime
-    Accessed, // accessed, atime, access
+    Modified,        // modified, mtime, mod, modification
+    Created,         // created, ctime, birth, birthtime
+    Accessed,        // accessed, atime, access
+    TreeNewestMtime, // tree_newest_mtime - newest mtime in a directory's tree
 }
 
 /// Enum-type selectors (validated at parse time)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EnumSelector {
-    Type, // type, filetype - file type enum
+    Type,     // type, filetype - file type enum
+    User,     // user, owner - user name or uid, resolved at typecheck
+    Group,    // group - group name or gid, resolved at typecheck
+    FsType,   // fstype - filesystem type enum
+    Lang,     // lang, language - programming language enum
+    Encoding, // encoding - text encoding, from a BOM or the bytes
+    Eol,      // eol - line endings used by a file
+    Ftype,    // ftype - named glob set, from the built-in table or config
 }
 
 // ============================================================================
@@ -164,6 +237,7 @@ pub enum SelectorCategory {
     Temporal(TemporalSelector),
     Enum(EnumSelector),
     StructuredData(DataFormat, Vec<StructuredPathComponent>),
+    Xattr(String), // xattr:name - extended attribute value
 }
 
 /// A typed selector paired with its compatible operator
@@ -176,6 +250,7 @@ pub enum TypedSelector {
     Temporal(TemporalSelector, TemporalOperator),
     StructuredData(DataFormat, Vec<StructuredPathComponent>, StructuredOperator),
     StructuredDataString(DataFormat, Vec<StructuredPathComponent>, StringOperator),
+    Xattr(String, StringOperator),
 }
 
 /// Operators for structured data selectors
@@ -232,11 +307,79 @@ pub fn parse_structured_selector(
     Ok(Some((format, components)))
 }
 
+/// Parse a line range selector, like `lines[1..3]` or `lines[5]`
+///
+/// Returns the first and last line, numbered from 1, or `None` if the selector
+/// isn't a line range.
+///
+/// # Errors
+/// Returns `ParseError::InvalidLineRange` if the range is malformed or empty.
+pub fn parse_line_range_selector(s: &str) -> Result<Option<(u64, u64)>, ParseError> {
+    let Some(range) = s
+        .strip_prefix("lines[")
+        .and_then(|rest| rest.strip_suffix(']'))
+    else {
+        return Ok(None);
+    };
+    let invalid = |reason: &str| ParseError::InvalidLineRange {
+        range: range.to_string(),
+        reason: reason.to_string(),
+    };
+    let (first, last) = range.split_once("..").unwrap_or((range, range));
+    let (Ok(first), Ok(last)) = (first.parse::<u64>(), last.parse::<u64>()) else {
+        return Err(invalid("expected a line number or a range like 1..3"));
+    };
+    if first == 0 {
+        return Err(invalid("lines are numbered from 1"));
+    }
+    if first > last {
+        return Err(invalid("the range is empty"));
+    }
+    Ok(Some((first, last)))
+}
+
+/// Parse extended attribute selector prefix (xattr:)
+///
+/// Returns `Some(name)` for `xattr:user.origin`, `None` if the selector has no
+/// `xattr:` prefix.
+///
+/// # Errors
+/// Returns `XattrSelectorError` if the attribute name is empty.
+pub fn parse_xattr_selector(s: &str) -> Result<Option<String>, XattrSelectorError> {
+    let Some((prefix, name)) = s.split_once(':') else {
+        return Ok(None);
+    };
+    if !prefix.eq_ignore_ascii_case("xattr") {
+        return Ok(None);
+    }
+    if name.is_empty() {
+        return Err(XattrSelectorError {
+            reason: "attribute name is empty".to_string(),
+        });
+    }
+    Ok(Some(name.to_string()))
+}
+
 /// Parse a selector string into a typed selector category
 ///
 /// # Errors
 /// Returns `ParseError::UnknownSelector` if the selector name is not recognized.
 pub fn recognize_selector(s: &str) -> Result<SelectorCategory, ParseError> {
+    // Extended attributes share the prefix syntax of structured data
+    match parse_xattr_selector(s) {
+        Ok(Some(name)) => return Ok(SelectorCategory::Xattr(name)),
+        Ok(None) => {}
+        Err(XattrSelectorError { reason }) => {
+            return Err(ParseError::InvalidXattrName { reason });
+        }
+    }
+
+    if let Some((first, last)) = parse_line_range_selector(s)? {
+        return Ok(SelectorCategory::String(StringSelector::LineRange(
+            first, last,
+        )));
+    }
+
     // Check for structured data prefix first
     match parse_structured_selector(s) {
         Ok(Some((format, components))) => {
@@ -282,9 +425,41 @@ pub fn recognize_selector(s: &str) -> Result<SelectorCategory, ParseError> {
 
         // File Properties (3) + aliases
         "size" | "filesize" | "bytes" => Ok(SelectorCategory::Numeric(NumericSelector::Size)),
+        "disk_usage" | "du" => Ok(SelectorCategory::Numeric(NumericSelector::DiskUsage)),
+        "blocks" => Ok(SelectorCategory::Numeric(NumericSelector::Blocks)),
+        "entries" | "children" => Ok(SelectorCategory::Numeric(NumericSelector::Entries)),
         "type" | "filetype" => Ok(SelectorCategory::Enum(EnumSelector::Type)),
+        "fstype" => Ok(SelectorCategory::Enum(EnumSelector::FsType)),
+        "selinux" => Ok(SelectorCategory::String(StringSelector::Selinux)),
+
+        // Ownership
+        "user" | "owner" => Ok(SelectorCategory::Enum(EnumSelector::User)),
+        "group" => Ok(SelectorCategory::Enum(EnumSelector::Group)),
+        "uid" => Ok(SelectorCategory::Numeric(NumericSelector::Uid)),
+        "gid" => Ok(SelectorCategory::Numeric(NumericSelector::Gid)),
+
+        // Filesystem identity
+        "inode" | "ino" => Ok(SelectorCategory::Numeric(NumericSelector::Inode)),
+        "nlink" | "links" => Ok(SelectorCategory::Numeric(NumericSelector::Nlink)),
+        "device" | "dev" => Ok(SelectorCategory::Numeric(NumericSelector::Device)),
         "depth" => Ok(SelectorCategory::Numeric(NumericSelector::Depth)),
 
+        // Directory tree aggregates, computed during the walk
+        "tree_size" => Ok(SelectorCategory::Numeric(NumericSelector::TreeSize)),
+        "tree_files" => Ok(SelectorCategory::Numeric(NumericSelector::TreeFiles)),
+        "lines" => Ok(SelectorCategory::Numeric(NumericSelector::Lines)),
+        "max_line_length" => Ok(SelectorCategory::Numeric(NumericSelector::MaxLineLength)),
+        "avg_line_length" => Ok(SelectorCategory::Numeric(NumericSelector::AvgLineLength)),
+        "tree_newest_mtime" => Ok(SelectorCategory::Temporal(
+            TemporalSelector::TreeNewestMtime,
+        )),
+
+        // Permissions
+        "mode" => Ok(SelectorCategory::Numeric(NumericSelector::Mode)),
+        "perm" | "perms" | "permissions" => {
+            Ok(SelectorCategory::String(StringSelector::Permissions))
+        }
+
         // Time (3) + common Unix aliases
         "modified" | "mtime" => Ok(SelectorCategory::Temporal(TemporalSelector::Modified)),
         "created" | "ctime" => Ok(SelectorCategory::Temporal(TemporalSelector::Created)),
@@ -292,6 +467,21 @@ pub fn recognize_selector(s: &str) -> Result<SelectorCategory, ParseError> {
 
         // Content (1) + aliases
         "content" | "contents" | "text" => Ok(SelectorCategory::String(StringSelector::Contents)),
+        "line" => Ok(SelectorCategory::String(StringSelector::Line)),
+        "all_lines" => Ok(SelectorCategory::String(StringSelector::AllLines)),
+        "sha256" => Ok(SelectorCategory::String(StringSelector::Hash(
+            HashAlgorithm::Sha256,
+        ))),
+        "blake3" => Ok(SelectorCategory::String(StringSelector::Hash(
+            HashAlgorithm::Blake3,
+        ))),
+        "mime" | "mimetype" => Ok(SelectorCategory::String(StringSelector::Mime)),
+        "shebang" => Ok(SelectorCategory::String(StringSelector::Shebang)),
+        "interpreter" => Ok(SelectorCategory::String(StringSelector::Interpreter)),
+        "lang" | "language" => Ok(SelectorCategory::Enum(EnumSelector::Lang)),
+        "encoding" => Ok(SelectorCategory::Enum(EnumSelector::Encoding)),
+        "eol" => Ok(SelectorCategory::Enum(EnumSelector::Eol)),
+        "ftype" => Ok(SelectorCategory::Enum(EnumSelector::Ftype)),
 
         // Everything else is unknown
         _ => Err(ParseError::UnknownSelector(s.to_string())),
@@ -327,6 +517,7 @@ pub fn parse_numeric_operator(s: &str) -> Result<NumericOperator, ParseError> {
         ">=" | "=>" | "gte" | "ge" => Ok(NumericOperator::GreaterOrEqual),
         "<" | "lt" => Ok(NumericOperator::Less),
         "<=" | "=<" | "lte" | "le" => Ok(NumericOperator::LessOrEqual),
+        "&" => Ok(NumericOperator::AnyBits),
         _ => Err(ParseError::UnknownOperator(s.to_string())),
     }
 }
@@ -419,6 +610,17 @@ pub fn parse_selector_operator(
             src: source.to_string(),
             suggestions: Some("Valid formats: yaml, json, toml".to_string()),
         },
+        ParseError::InvalidXattrName { reason } => DetectError::InvalidXattrName {
+            span: selector_span.to_source_span(),
+            reason,
+            src: source.to_string(),
+        },
+        ParseError::InvalidLineRange { range, reason } => DetectError::InvalidLineRange {
+            range,
+            span: selector_span.to_source_span(),
+            reason,
+            src: source.to_string(),
+        },
         ParseError::UnknownSelector(_) => DetectError::UnknownSelector {
             selector: selector_str.to_string(),
             span: selector_span.to_source_span(),
@@ -477,8 +679,8 @@ pub fn parse_selector_operator(
                 }
             })?;
 
-            // Special validation: Contents doesn't support 'in' or '!='
-            if matches!(selector, StringSelector::Contents) {
+            // Special validation: content patterns don't support 'in' or '!='
+            if selector.is_content_pattern() {
                 match operator {
                     StringOperator::In | StringOperator::NotEquals => {
                         return Err(DetectError::IncompatibleOperator {
@@ -493,6 +695,19 @@ pub fn parse_selector_operator(
                 }
             }
 
+            // Digests are compared whole: == or in
+            if matches!(selector, StringSelector::Hash(_))
+                && !matches!(operator, StringOperator::Equals | StringOperator::In)
+            {
+                return Err(DetectError::IncompatibleOperator {
+                    selector: selector_str.to_string(),
+                    operator: operator_str.to_string(),
+                    selector_span: selector_span.to_source_span(),
+                    operator_span: operator_span.to_source_span(),
+                    src: source.to_string(),
+                });
+            }
+
             Ok(TypedSelector::String(selector, operator))
         }
 
@@ -514,6 +729,18 @@ pub fn parse_selector_operator(
                     }
                 }
             })?;
+
+            // Bit masks only make sense for permission bits
+            if operator == NumericOperator::AnyBits && selector != NumericSelector::Mode {
+                return Err(DetectError::IncompatibleOperator {
+                    selector: selector_str.to_string(),
+                    operator: operator_str.to_string(),
+                    selector_span: selector_span.to_source_span(),
+                    operator_span: operator_span.to_source_span(),
+                    src: source.to_string(),
+                });
+            }
+
             Ok(TypedSelector::Numeric(selector, operator))
         }
 
@@ -583,6 +810,27 @@ pub fn parse_selector_operator(
                 Ok(TypedSelector::StructuredData(format, components, operator))
             }
         }
+
+        SelectorCategory::Xattr(name) => {
+            let operator = parse_string_operator(operator_str).map_err(|_| {
+                if is_known_operator {
+                    DetectError::IncompatibleOperator {
+                        selector: selector_str.to_string(),
+                        operator: operator_str.to_string(),
+                        selector_span: selector_span.to_source_span(),
+                        operator_span: operator_span.to_source_span(),
+                        src: source.to_string(),
+                    }
+                } else {
+                    DetectError::UnknownOperator {
+                        operator: operator_str.to_string(),
+                        span: operator_span.to_source_span(),
+                        src: source.to_string(),
+                    }
+                }
+            })?;
+            Ok(TypedSelector::Xattr(name, operator))
+        }
     }
 }
 
@@ -596,6 +844,20 @@ impl NumericSelector {
         match self {
             NumericSelector::Size => "size",
             NumericSelector::Depth => "depth",
+            NumericSelector::Entries => "entries",
+            NumericSelector::Mode => "mode",
+            NumericSelector::DiskUsage => "disk_usage",
+            NumericSelector::Blocks => "blocks",
+            NumericSelector::Uid => "uid",
+            NumericSelector::Gid => "gid",
+            NumericSelector::Inode => "inode",
+            NumericSelector::Nlink => "nlink",
+            NumericSelector::Device => "device",
+            NumericSelector::TreeSize => "tree_size",
+            NumericSelector::TreeFiles => "tree_files",
+            NumericSelector::Lines => "lines",
+            NumericSelector::MaxLineLength => "max_line_length",
+            NumericSelector::AvgLineLength => "avg_line_length",
         }
     }
 }
@@ -607,6 +869,7 @@ impl TemporalSelector {
             TemporalSelector::Modified => "modified",
             TemporalSelector::Created => "created",
             TemporalSelector::Accessed => "accessed",
+            TemporalSelector::TreeNewestMtime => "tree_newest_mtime",
         }
     }
 }
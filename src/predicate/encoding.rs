@@ -0,0 +1,224 @@
+//! Text encodings, for `encoding == utf16le` and for transcoding content
+//! before it reaches content patterns
+//!
+//! Content patterns match UTF-8, so UTF-16 and Latin-1 files are decoded on
+//! the fly. Digests still see the raw bytes.
+
+use std::borrow::Cow;
+
+use super::EnumPredicate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+impl TextEncoding {
+    fn all_variants() -> &'static [Self] {
+        &[Self::Utf8, Self::Utf16Le, Self::Utf16Be, Self::Latin1]
+    }
+
+    /// The byte order mark for this encoding, if it has one
+    fn bom(self) -> Option<&'static [u8]> {
+        match self {
+            TextEncoding::Utf8 => Some(b"\xef\xbb\xbf"),
+            TextEncoding::Utf16Le => Some(b"\xff\xfe"),
+            TextEncoding::Utf16Be => Some(b"\xfe\xff"),
+            TextEncoding::Latin1 => None,
+        }
+    }
+
+    /// The encoding named by a byte order mark at the start of `header`
+    pub fn from_bom(header: &[u8]) -> Option<Self> {
+        Self::all_variants()
+            .iter()
+            .copied()
+            .find(|e| e.bom().is_some_and(|bom| header.starts_with(bom)))
+    }
+
+    /// Detect the encoding of a file from its first block: a byte order mark,
+    /// else UTF-8 if it's valid, else Latin-1 if there are no NUL bytes
+    ///
+    /// Files that look binary have no encoding.
+    pub fn detect(header: &[u8]) -> Option<Self> {
+        if let Some(encoding) = Self::from_bom(header) {
+            return Some(encoding);
+        }
+        if header.contains(&0) {
+            None
+        } else if !super::is_binary(header) {
+            Some(TextEncoding::Utf8)
+        } else {
+            Some(TextEncoding::Latin1)
+        }
+    }
+
+    fn decoder(self) -> Option<encoding_rs::Decoder> {
+        let encoding = match self {
+            TextEncoding::Utf8 => return None,
+            TextEncoding::Utf16Le => encoding_rs::UTF_16LE,
+            TextEncoding::Utf16Be => encoding_rs::UTF_16BE,
+            // Browsers and encoding_rs treat ISO-8859-1 as windows-1252
+            TextEncoding::Latin1 => encoding_rs::WINDOWS_1252,
+        };
+        Some(encoding.new_decoder_without_bom_handling())
+    }
+}
+
+impl EnumPredicate for TextEncoding {
+    fn from_str(s: &str) -> Result<Self, String> {
+        let s_lower = s.to_lowercase();
+        for variant in Self::all_variants() {
+            if variant.aliases().contains(&s_lower.as_str()) {
+                return Ok(*variant);
+            }
+        }
+        Err(format!(
+            "Unknown encoding '{}'. Valid encodings: {}",
+            s,
+            Self::all_valid_strings().join(", ")
+        ))
+    }
+
+    fn all_valid_strings() -> &'static [&'static str] {
+        &[
+            "utf8",
+            "utf-8",
+            "utf16le",
+            "utf-16le",
+            "utf16be",
+            "utf-16be",
+            "latin1",
+            "iso-8859-1",
+        ]
+    }
+
+    fn as_str(&self) -> &'static str {
+        self.aliases()[0]
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            TextEncoding::Utf8 => &["utf8", "utf-8"],
+            TextEncoding::Utf16Le => &["utf16le", "utf-16le"],
+            TextEncoding::Utf16Be => &["utf16be", "utf-16be"],
+            TextEncoding::Latin1 => &["latin1", "iso-8859-1"],
+        }
+    }
+}
+
+/// Transcodes a file's contents to UTF-8, one chunk at a time
+///
+/// With no forced encoding the first chunk is sniffed for a byte order mark,
+/// and files without one pass through untouched. Byte order marks are
+/// always stripped.
+pub struct ContentDecoder {
+    forced: Option<TextEncoding>,
+    state: DecoderState,
+}
+
+enum DecoderState {
+    Start,
+    Passthrough,
+    Decode(encoding_rs::Decoder),
+}
+
+impl ContentDecoder {
+    pub fn new(forced: Option<TextEncoding>) -> Self {
+        Self {
+            forced,
+            state: DecoderState::Start,
+        }
+    }
+
+    /// Decode the next chunk; `last` flushes a character cut off at the end
+    pub fn decode<'b>(&mut self, mut chunk: &'b [u8], last: bool) -> Cow<'b, [u8]> {
+        if let DecoderState::Start = self.state {
+            let encoding = self
+                .forced
+                .or_else(|| TextEncoding::from_bom(chunk))
+                .unwrap_or(TextEncoding::Utf8);
+            if let Some(bom) = encoding.bom() {
+                chunk = chunk.strip_prefix(bom).unwrap_or(chunk);
+            }
+            self.state = match encoding.decoder() {
+                Some(decoder) => DecoderState::Decode(decoder),
+                None => DecoderState::Passthrough,
+            };
+        }
+
+        match &mut self.state {
+            DecoderState::Start | DecoderState::Passthrough => Cow::Borrowed(chunk),
+            DecoderState::Decode(decoder) => {
+                let capacity = decoder
+                    .max_utf8_buffer_length(chunk.len())
+                    .unwrap_or(chunk.len() * 3);
+                let mut out = String::with_capacity(capacity);
+                let _ = decoder.decode_to_string(chunk, &mut out, last);
+                Cow::Owned(out.into_bytes())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(s: &str) -> Vec<u8> {
+        let mut bytes = b"\xff\xfe".to_vec();
+        bytes.extend(s.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            TextEncoding::detect(&utf16le("hi")),
+            Some(TextEncoding::Utf16Le)
+        );
+        assert_eq!(
+            TextEncoding::detect(b"\xfe\xff\0h"),
+            Some(TextEncoding::Utf16Be)
+        );
+        assert_eq!(
+            TextEncoding::detect(b"\xef\xbb\xbfhi"),
+            Some(TextEncoding::Utf8)
+        );
+        assert_eq!(
+            TextEncoding::detect("caf\u{e9}".as_bytes()),
+            Some(TextEncoding::Utf8)
+        );
+        assert_eq!(TextEncoding::detect(b"caf\xe9"), Some(TextEncoding::Latin1));
+        assert_eq!(TextEncoding::detect(b"\0\x01\x02"), None);
+    }
+
+    #[test]
+    fn test_decode_split_chunks() {
+        let bytes = utf16le("TODO: caf\u{e9}");
+        // Split in the middle of a code unit
+        let (a, b) = bytes.split_at(7);
+
+        let mut decoder = ContentDecoder::new(None);
+        let mut out = decoder.decode(a, false).into_owned();
+        out.extend_from_slice(&decoder.decode(b, false));
+        out.extend_from_slice(&decoder.decode(&[], true));
+        assert_eq!(out, "TODO: caf\u{e9}".as_bytes());
+    }
+
+    #[test]
+    fn test_decode_passthrough_and_forced() {
+        let mut decoder = ContentDecoder::new(None);
+        assert_eq!(&*decoder.decode(b"\xef\xbb\xbfplain", true), b"plain");
+
+        let mut decoder = ContentDecoder::new(Some(TextEncoding::Latin1));
+        assert_eq!(&*decoder.decode(b"caf\xe9", true), "caf\u{e9}".as_bytes());
+
+        // A forced encoding doesn't need a byte order mark
+        let mut decoder = ContentDecoder::new(Some(TextEncoding::Utf16Le));
+        assert_eq!(&*decoder.decode(b"h\0i\0", true), b"hi");
+    }
+}
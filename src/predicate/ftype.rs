@@ -0,0 +1,182 @@
+//! Named file types for `ftype == rust`, like ripgrep's `--type`
+//!
+//! Each type is a set of globs matched against the file name. The built-in
+//! table is ripgrep's, plus a few broader groups, and a config file can add
+//! or replace types.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use ignore::types::{Types, TypesBuilder};
+
+/// Groups spanning several ripgrep types
+const EXTRA_TYPES: &[(&str, &[&str])] = &[
+    ("web", &["*.{js,ts,jsx,tsx,css,html}"]),
+    (
+        "config",
+        &[
+            "*.{cfg,conf,config,ini}",
+            "*.{toml,yaml,yml,json}",
+            "*.properties",
+        ],
+    ),
+];
+
+/// File type definitions: type name to globs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileTypeDefs {
+    defs: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for FileTypeDefs {
+    fn default() -> Self {
+        let mut defs: BTreeMap<String, Vec<String>> = TypesBuilder::new()
+            .add_defaults()
+            .definitions()
+            .into_iter()
+            .map(|def| (def.name().to_string(), def.globs().to_vec()))
+            .collect();
+        for (name, globs) in EXTRA_TYPES {
+            defs.insert(
+                name.to_string(),
+                globs.iter().map(|g| g.to_string()).collect(),
+            );
+        }
+        Self { defs }
+    }
+}
+
+impl FileTypeDefs {
+    /// Add a type, replacing any type of the same name
+    ///
+    /// Names are letters and digits only, as in ripgrep.
+    pub fn insert(&mut self, name: &str, globs: Vec<String>) -> Result<(), String> {
+        if name.is_empty() || !name.chars().all(char::is_alphanumeric) {
+            return Err(format!(
+                "Invalid file type name '{name}': use only letters and digits"
+            ));
+        }
+        if globs.is_empty() {
+            return Err(format!("File type '{name}' has no globs"));
+        }
+
+        // Globs are compiled when a type is selected
+        let mut builder = TypesBuilder::new();
+        for glob in &globs {
+            builder.add(name, glob).map_err(|e| e.to_string())?;
+        }
+        builder
+            .select(name)
+            .build()
+            .map_err(|e| format!("Invalid glob for file type '{name}': {e}"))?;
+
+        self.defs.insert(name.to_string(), globs);
+        Ok(())
+    }
+
+    /// Add the types from the `[types]` table of a TOML config file:
+    ///
+    /// ```toml
+    /// [types]
+    /// web = ["*.js", "*.ts", "*.vue"]  # replaces the built-in `web`
+    /// proto = "*.proto"
+    /// ```
+    pub fn extend_from_toml(&mut self, source: &str) -> Result<(), String> {
+        let config: toml::Table = source.parse().map_err(|e| format!("{e}"))?;
+        let Some(types) = config.get("types") else {
+            return Ok(());
+        };
+        let types = types
+            .as_table()
+            .ok_or("`types` must be a table of type names to globs")?;
+
+        for (name, value) in types {
+            let globs = match value {
+                toml::Value::String(glob) => vec![glob.clone()],
+                toml::Value::Array(globs) => globs
+                    .iter()
+                    .map(|g| g.as_str().map(str::to_string))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| format!("Globs for file type '{name}' must be strings"))?,
+                _ => {
+                    return Err(format!(
+                        "File type '{name}' must be a glob or a list of globs"
+                    ))
+                }
+            };
+            self.insert(name, globs)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `name` is a defined type
+    pub fn contains(&self, name: &str) -> bool {
+        self.defs.contains_key(name)
+    }
+
+    /// All type names, sorted
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.defs.keys().map(String::as_str)
+    }
+}
+
+/// Matcher for `ftype == rust`, `ftype != web` and `ftype in [web, config]`
+#[derive(Clone)]
+pub struct FileTypeMatcher {
+    names: Vec<String>,
+    negated: bool,
+    types: Types,
+}
+
+impl FileTypeMatcher {
+    /// Build a matcher for files of any of the `names` types, or of none of
+    /// them if `negated`
+    ///
+    /// Returns the first name that isn't defined in `defs` as the error.
+    pub fn new(defs: &FileTypeDefs, names: Vec<String>, negated: bool) -> Result<Self, String> {
+        let mut builder = TypesBuilder::new();
+        for name in &names {
+            let globs = defs.defs.get(name).ok_or_else(|| name.clone())?;
+            for glob in globs {
+                builder
+                    .add(name, glob)
+                    .expect("file type names and globs are validated when defined");
+            }
+            builder.select(name);
+        }
+        let types = builder
+            .build()
+            .expect("file type names and globs are validated when defined");
+
+        let mut names = names;
+        names.sort();
+        names.dedup();
+        Ok(Self {
+            names,
+            negated,
+            types,
+        })
+    }
+
+    pub fn is_match(&self, path: &Path) -> bool {
+        self.types.matched(path, false).is_whitelist() != self.negated
+    }
+}
+
+impl PartialEq for FileTypeMatcher {
+    fn eq(&self, other: &Self) -> bool {
+        self.names == other.names && self.negated == other.negated
+    }
+}
+
+impl Eq for FileTypeMatcher {}
+
+impl fmt::Debug for FileTypeMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileTypeMatcher")
+            .field("names", &self.names)
+            .field("negated", &self.negated)
+            .finish()
+    }
+}
@@ -0,0 +1,104 @@
+//! Content digests, for `sha256 == ...`, `blake3 in [...]` and `content_eq(...)`
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use sha2::Digest as _;
+
+pub use crate::parser::typed::HashAlgorithm;
+
+/// A 32 byte digest, the output size of both sha256 and blake3
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Digest(pub [u8; 32]);
+
+impl Digest {
+    /// Parse a 64 character hex digest, in either case
+    pub fn from_hex(s: &str) -> Option<Self> {
+        if s.len() != 64 || !s.is_ascii() {
+            return None;
+        }
+        let mut bytes = [0; 32];
+        for (byte, pair) in bytes.iter_mut().zip(s.as_bytes().chunks(2)) {
+            let pair = std::str::from_utf8(pair).ok()?;
+            *byte = u8::from_str_radix(pair, 16).ok()?;
+        }
+        Some(Self(bytes))
+    }
+}
+
+// Digests show up in debug logs of the parsed expression
+impl fmt::Debug for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{b:02x}"))
+    }
+}
+
+/// `sha256 == <hex>`, `blake3 in [...]`: the file's digest is one of `digests`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashPredicate {
+    pub algorithm: HashAlgorithm,
+    pub digests: Vec<Digest>,
+}
+
+impl HashPredicate {
+    pub fn is_match(&self, digest: &Digest) -> bool {
+        self.digests.contains(digest)
+    }
+}
+
+/// Incremental hashing of a file's contents as they stream past
+#[derive(Clone)]
+pub enum Hasher {
+    Sha256(sha2::Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => Self::Sha256(sha2::Sha256::new()),
+            HashAlgorithm::Blake3 => Self::Blake3(Box::default()),
+        }
+    }
+
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self {
+            Self::Sha256(_) => HashAlgorithm::Sha256,
+            Self::Blake3(_) => HashAlgorithm::Blake3,
+        }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Sha256(h) => h.update(bytes),
+            Self::Blake3(h) => {
+                h.update(bytes);
+            }
+        }
+    }
+
+    pub fn finalize(self) -> Digest {
+        match self {
+            Self::Sha256(h) => Digest(h.finalize().into()),
+            Self::Blake3(h) => Digest(*h.finalize().as_bytes()),
+        }
+    }
+}
+
+/// Hash everything `reader` produces
+pub fn hash_reader(algorithm: HashAlgorithm, mut reader: impl io::Read) -> io::Result<Digest> {
+    let mut hasher = Hasher::new(algorithm);
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        match reader.read(&mut buf)? {
+            0 => return Ok(hasher.finalize()),
+            n => hasher.update(&buf[..n]),
+        }
+    }
+}
+
+/// Hash a whole file, e.g. the reference file of `content_eq(...)`
+pub fn hash_file(algorithm: HashAlgorithm, path: &Path) -> io::Result<Digest> {
+    hash_reader(algorithm, std::fs::File::open(path)?)
+}
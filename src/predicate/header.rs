@@ -0,0 +1,112 @@
+//! Predicates decided from the first block of a file, which is read once
+//! before (and often instead of) a full content scan
+
+use std::path::Path;
+
+use super::encoding::TextEncoding;
+use super::lang::{shebang_interpreter, Language};
+use super::mime::detect_mime;
+use super::{EnumMatcher, StringMatcher};
+
+/// How much of each file the header phase reads
+pub const HEADER_SIZE: usize = 8192;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderPredicate {
+    /// `text`
+    Text,
+    /// `binary`
+    Binary,
+    /// `mime == "image/png"`, from magic numbers
+    Mime(StringMatcher),
+    /// `lang == rust`, from the file name or else the first line
+    Lang(EnumMatcher<Language>),
+    /// `shebang ~= python3`, the first line after `#!`
+    Shebang(StringMatcher),
+    /// `interpreter == bash`, the program a shebang runs, after any `env`
+    Interpreter(StringMatcher),
+    /// `encoding == utf16le`, from a byte order mark or else the bytes
+    Encoding(EnumMatcher<TextEncoding>),
+    /// `bom`, starts with a byte order mark
+    Bom,
+}
+
+impl HeaderPredicate {
+    /// Decide the predicate from the path alone, if possible, so the header
+    /// doesn't need to be read
+    pub fn eval_path(&self, path: &Path) -> Option<bool> {
+        match self {
+            HeaderPredicate::Lang(matcher) => {
+                Language::from_path(path).map(|lang| matcher.is_match(&lang))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_match(&self, path: &Path, header: &[u8]) -> bool {
+        match self {
+            HeaderPredicate::Text => !is_binary(header),
+            HeaderPredicate::Binary => is_binary(header),
+            HeaderPredicate::Mime(matcher) => matcher.is_match(detect_mime(header)),
+            HeaderPredicate::Lang(matcher) => {
+                let language = Language::from_path(path)
+                    .or_else(|| Language::from_first_line(first_line(header)?));
+                // Files in no known language only match `!=`
+                match language {
+                    Some(language) => matcher.is_match(&language),
+                    None => matches!(matcher, EnumMatcher::NotEquals(_)),
+                }
+            }
+            HeaderPredicate::Shebang(matcher) => {
+                let shebang = first_line(header).and_then(|line| line.strip_prefix("#!"));
+                match_optional(matcher, shebang.map(str::trim))
+            }
+            HeaderPredicate::Interpreter(matcher) => {
+                match_optional(matcher, first_line(header).and_then(shebang_interpreter))
+            }
+            // Binary files only match `!=`
+            HeaderPredicate::Encoding(matcher) => match TextEncoding::detect(header) {
+                Some(encoding) => matcher.is_match(&encoding),
+                None => matches!(matcher, EnumMatcher::NotEquals(_)),
+            },
+            HeaderPredicate::Bom => TextEncoding::from_bom(header).is_some(),
+        }
+    }
+}
+
+/// The first line of a file, without its line ending
+fn first_line(header: &[u8]) -> Option<&str> {
+    let line = header.split(|&b| b == b'\n').next()?;
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    std::str::from_utf8(line).ok()
+}
+
+/// Files without a value, like scripts without a shebang, only match `!=`
+fn match_optional(matcher: &StringMatcher, value: Option<&str>) -> bool {
+    match value {
+        Some(value) => matcher.is_match(value),
+        None => matches!(matcher, StringMatcher::NotEquals(_)),
+    }
+}
+
+/// Whether a file looks binary from its first block: it contains a NUL byte
+/// or isn't valid UTF-8
+///
+/// A multi-byte character cut off by the end of a full block doesn't count
+/// against the file, and files starting with a UTF-16 byte order mark are
+/// text.
+pub fn is_binary(header: &[u8]) -> bool {
+    if matches!(
+        TextEncoding::from_bom(header),
+        Some(TextEncoding::Utf16Le | TextEncoding::Utf16Be)
+    ) {
+        return false;
+    }
+    if header.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(header) {
+        Ok(_) => false,
+        Err(e) => e.error_len().is_some() || header.len() < HEADER_SIZE,
+    }
+}
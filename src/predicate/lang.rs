@@ -0,0 +1,488 @@
+//! Programming language detection for `lang == rust`
+//!
+//! A file's language comes from its extension or a well-known file name when
+//! possible. Otherwise the first line is checked for a shebang or an editor
+//! modeline, so extensionless scripts are classified too.
+
+use std::path::Path;
+
+use super::EnumPredicate;
+
+/// Programming language enumeration for `lang` predicates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    Awk,
+    C,
+    Cmake,
+    Cpp,
+    Csharp,
+    Css,
+    Dart,
+    Dockerfile,
+    Elisp,
+    Elixir,
+    Erlang,
+    Go,
+    Haskell,
+    Html,
+    Java,
+    Javascript,
+    Json,
+    Julia,
+    Kotlin,
+    Lua,
+    Make,
+    Markdown,
+    Nix,
+    Ocaml,
+    Perl,
+    Php,
+    Powershell,
+    Protobuf,
+    Python,
+    R,
+    Ruby,
+    Rust,
+    Scala,
+    Shell,
+    Sql,
+    Swift,
+    Tcl,
+    Terraform,
+    Toml,
+    Typescript,
+    Vim,
+    Xml,
+    Yaml,
+    Zig,
+}
+
+impl Language {
+    /// All enum variants for iteration
+    fn all_variants() -> &'static [Self] {
+        &[
+            Self::Awk,
+            Self::C,
+            Self::Cmake,
+            Self::Cpp,
+            Self::Csharp,
+            Self::Css,
+            Self::Dart,
+            Self::Dockerfile,
+            Self::Elisp,
+            Self::Elixir,
+            Self::Erlang,
+            Self::Go,
+            Self::Haskell,
+            Self::Html,
+            Self::Java,
+            Self::Javascript,
+            Self::Json,
+            Self::Julia,
+            Self::Kotlin,
+            Self::Lua,
+            Self::Make,
+            Self::Markdown,
+            Self::Nix,
+            Self::Ocaml,
+            Self::Perl,
+            Self::Php,
+            Self::Powershell,
+            Self::Protobuf,
+            Self::Python,
+            Self::R,
+            Self::Ruby,
+            Self::Rust,
+            Self::Scala,
+            Self::Shell,
+            Self::Sql,
+            Self::Swift,
+            Self::Tcl,
+            Self::Terraform,
+            Self::Toml,
+            Self::Typescript,
+            Self::Vim,
+            Self::Xml,
+            Self::Yaml,
+            Self::Zig,
+        ]
+    }
+
+    /// Language implied by a file's name alone, from well-known file names
+    /// and then the extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let by_name = match name {
+            "Dockerfile" | "Containerfile" => Some(Self::Dockerfile),
+            "Makefile" | "makefile" | "GNUmakefile" => Some(Self::Make),
+            "CMakeLists.txt" => Some(Self::Cmake),
+            "Rakefile" | "Gemfile" | "Vagrantfile" | "Podfile" => Some(Self::Ruby),
+            ".bashrc" | ".bash_profile" | ".profile" | ".zshrc" | ".zprofile" => Some(Self::Shell),
+            ".vimrc" => Some(Self::Vim),
+            _ if name.starts_with("Dockerfile.") => Some(Self::Dockerfile),
+            _ => None,
+        };
+        by_name.or_else(|| Self::from_extension(path.extension()?.to_str()?))
+    }
+
+    fn from_extension(ext: &str) -> Option<Self> {
+        let language = match ext.to_lowercase().as_str() {
+            "awk" => Self::Awk,
+            "c" | "h" => Self::C,
+            "cmake" => Self::Cmake,
+            "cc" | "cpp" | "cxx" | "c++" | "hh" | "hpp" | "hxx" => Self::Cpp,
+            "cs" => Self::Csharp,
+            "css" | "scss" | "sass" | "less" => Self::Css,
+            "dart" => Self::Dart,
+            "dockerfile" => Self::Dockerfile,
+            "el" => Self::Elisp,
+            "ex" | "exs" => Self::Elixir,
+            "erl" | "hrl" => Self::Erlang,
+            "go" => Self::Go,
+            "hs" | "lhs" => Self::Haskell,
+            "html" | "htm" | "xhtml" => Self::Html,
+            "java" => Self::Java,
+            "js" | "mjs" | "cjs" | "jsx" => Self::Javascript,
+            "json" => Self::Json,
+            "jl" => Self::Julia,
+            "kt" | "kts" => Self::Kotlin,
+            "lua" => Self::Lua,
+            "mk" | "mak" => Self::Make,
+            "md" | "markdown" => Self::Markdown,
+            "nix" => Self::Nix,
+            "ml" | "mli" => Self::Ocaml,
+            "pl" | "pm" => Self::Perl,
+            "php" => Self::Php,
+            "ps1" | "psm1" => Self::Powershell,
+            "proto" => Self::Protobuf,
+            "py" | "pyi" | "pyw" => Self::Python,
+            "r" => Self::R,
+            "rb" | "rake" | "gemspec" => Self::Ruby,
+            "rs" => Self::Rust,
+            "scala" | "sc" => Self::Scala,
+            "sh" | "bash" | "zsh" | "ksh" => Self::Shell,
+            "sql" => Self::Sql,
+            "swift" => Self::Swift,
+            "tcl" => Self::Tcl,
+            "tf" | "tfvars" | "hcl" => Self::Terraform,
+            "toml" => Self::Toml,
+            "ts" | "mts" | "cts" | "tsx" => Self::Typescript,
+            "vim" => Self::Vim,
+            "xml" | "xsd" | "xsl" | "svg" => Self::Xml,
+            "yaml" | "yml" => Self::Yaml,
+            "zig" => Self::Zig,
+            _ => return None,
+        };
+        Some(language)
+    }
+
+    /// Language declared by a file's first line: a shebang like
+    /// `#!/usr/bin/env python3`, or a modeline like `# vim: ft=sh` or
+    /// `-*- mode: ruby -*-`
+    pub fn from_first_line(line: &str) -> Option<Self> {
+        if let Some(interpreter) = shebang_interpreter(line) {
+            return Self::from_interpreter(interpreter);
+        }
+        modeline_language(line)
+    }
+
+    /// Language of a script run by `interpreter`, with version suffixes like
+    /// `python3.12` ignored
+    fn from_interpreter(interpreter: &str) -> Option<Self> {
+        let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        let language = match name {
+            "sh" | "bash" | "dash" | "ash" | "ksh" | "mksh" | "zsh" => Self::Shell,
+            "python" | "pypy" => Self::Python,
+            "node" | "nodejs" | "deno" | "bun" => Self::Javascript,
+            "ts-node" => Self::Typescript,
+            "awk" | "gawk" | "mawk" | "nawk" => Self::Awk,
+            "tclsh" | "wish" => Self::Tcl,
+            "Rscript" => Self::R,
+            "pwsh" => Self::Powershell,
+            "escript" => Self::Erlang,
+            "runghc" | "runhaskell" => Self::Haskell,
+            "make" => Self::Make,
+            other => return Self::from_str(other).ok(),
+        };
+        Some(language)
+    }
+}
+
+/// The interpreter named by a shebang line, skipping `env` and its flags
+///
+/// `#!/usr/bin/env -S python3 -u` names `python3`.
+pub(crate) fn shebang_interpreter(line: &str) -> Option<&str> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    Some(program)
+}
+
+/// Language named by a vim or emacs modeline
+fn modeline_language(line: &str) -> Option<Language> {
+    // Emacs: `-*- mode: python -*-` or just `-*- python -*-`
+    if let Some((_, rest)) = line.split_once("-*-") {
+        let (vars, _) = rest.split_once("-*-")?;
+        let mode = vars
+            .split(';')
+            .find_map(|var| match var.split_once(':') {
+                Some((key, value)) => (key.trim() == "mode").then_some(value),
+                None => Some(var),
+            })?
+            .trim();
+        return emacs_mode_language(mode);
+    }
+
+    // Vim: `vim: set ft=python:`, `vi: filetype=sh`, `ex: syntax=ruby`, with
+    // the marker at the start of the line or after whitespace
+    let settings = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker).find_map(|(i, _)| {
+            let at_word_start = line[..i]
+                .chars()
+                .next_back()
+                .map_or(true, char::is_whitespace);
+            at_word_start.then(|| &line[i + marker.len()..])
+        })
+    })?;
+    settings
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .find_map(|setting| {
+            let (key, value) = setting.split_once('=')?;
+            matches!(key, "ft" | "filetype" | "syntax").then_some(value)
+        })
+        .and_then(|ft| Language::from_str(ft).ok())
+}
+
+fn emacs_mode_language(mode: &str) -> Option<Language> {
+    match mode.to_lowercase().as_str() {
+        "emacs-lisp" | "lisp-interaction" => Some(Language::Elisp),
+        "c++" => Some(Language::Cpp),
+        "js" | "js2" => Some(Language::Javascript),
+        "shell-script" => Some(Language::Shell),
+        "makefile" | "makefile-gmake" => Some(Language::Make),
+        mode => Language::from_str(mode).ok(),
+    }
+}
+
+impl EnumPredicate for Language {
+    fn from_str(s: &str) -> Result<Self, String> {
+        let s_lower = s.to_lowercase();
+        for variant in Self::all_variants() {
+            if variant.aliases().contains(&s_lower.as_str()) {
+                return Ok(*variant);
+            }
+        }
+        Err(format!(
+            "Unknown language '{}'. Valid languages: {}",
+            s,
+            Self::all_valid_strings().join(", ")
+        ))
+    }
+
+    fn all_valid_strings() -> &'static [&'static str] {
+        &[
+            "awk",
+            "c",
+            "cmake",
+            "cpp",
+            "c++",
+            "csharp",
+            "cs",
+            "css",
+            "dart",
+            "dockerfile",
+            "docker",
+            "elisp",
+            "elixir",
+            "erlang",
+            "go",
+            "golang",
+            "haskell",
+            "html",
+            "java",
+            "javascript",
+            "js",
+            "json",
+            "julia",
+            "kotlin",
+            "lua",
+            "make",
+            "makefile",
+            "markdown",
+            "md",
+            "nix",
+            "ocaml",
+            "perl",
+            "php",
+            "powershell",
+            "protobuf",
+            "proto",
+            "python",
+            "py",
+            "r",
+            "ruby",
+            "rb",
+            "rust",
+            "rs",
+            "scala",
+            "shell",
+            "sh",
+            "bash",
+            "zsh",
+            "sql",
+            "swift",
+            "tcl",
+            "terraform",
+            "hcl",
+            "toml",
+            "typescript",
+            "ts",
+            "vim",
+            "vimscript",
+            "xml",
+            "yaml",
+            "yml",
+            "zig",
+        ]
+    }
+
+    fn as_str(&self) -> &'static str {
+        self.aliases()[0]
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Language::Awk => &["awk"],
+            Language::C => &["c"],
+            Language::Cmake => &["cmake"],
+            Language::Cpp => &["cpp", "c++"],
+            Language::Csharp => &["csharp", "cs"],
+            Language::Css => &["css"],
+            Language::Dart => &["dart"],
+            Language::Dockerfile => &["dockerfile", "docker"],
+            Language::Elisp => &["elisp"],
+            Language::Elixir => &["elixir"],
+            Language::Erlang => &["erlang"],
+            Language::Go => &["go", "golang"],
+            Language::Haskell => &["haskell"],
+            Language::Html => &["html"],
+            Language::Java => &["java"],
+            Language::Javascript => &["javascript", "js"],
+            Language::Json => &["json"],
+            Language::Julia => &["julia"],
+            Language::Kotlin => &["kotlin"],
+            Language::Lua => &["lua"],
+            Language::Make => &["make", "makefile"],
+            Language::Markdown => &["markdown", "md"],
+            Language::Nix => &["nix"],
+            Language::Ocaml => &["ocaml"],
+            Language::Perl => &["perl"],
+            Language::Php => &["php"],
+            Language::Powershell => &["powershell"],
+            Language::Protobuf => &["protobuf", "proto"],
+            Language::Python => &["python", "py"],
+            Language::R => &["r"],
+            Language::Ruby => &["ruby", "rb"],
+            Language::Rust => &["rust", "rs"],
+            Language::Scala => &["scala"],
+            Language::Shell => &["shell", "sh", "bash", "zsh"],
+            Language::Sql => &["sql"],
+            Language::Swift => &["swift"],
+            Language::Tcl => &["tcl"],
+            Language::Terraform => &["terraform", "hcl"],
+            Language::Toml => &["toml"],
+            Language::Typescript => &["typescript", "ts"],
+            Language::Vim => &["vim", "vimscript"],
+            Language::Xml => &["xml"],
+            Language::Yaml => &["yaml", "yml"],
+            Language::Zig => &["zig"],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(
+            Language::from_path(Path::new("src/main.rs")),
+            Some(Language::Rust)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("a/Dockerfile")),
+            Some(Language::Dockerfile)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("Dockerfile.dev")),
+            Some(Language::Dockerfile)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("Makefile")),
+            Some(Language::Make)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("CMakeLists.txt")),
+            Some(Language::Cmake)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("App.TSX")),
+            Some(Language::Typescript)
+        );
+        assert_eq!(Language::from_path(Path::new("bin/deploy")), None);
+        assert_eq!(Language::from_path(Path::new("notes.txt")), None);
+    }
+
+    #[test]
+    fn test_shebang() {
+        assert_eq!(shebang_interpreter("#!/bin/bash"), Some("bash"));
+        assert_eq!(
+            shebang_interpreter("#! /usr/bin/env python3 -u"),
+            Some("python3")
+        );
+        assert_eq!(
+            shebang_interpreter("#!/usr/bin/env -S LANG=C node"),
+            Some("node")
+        );
+        assert_eq!(shebang_interpreter("# comment"), None);
+
+        let lang = Language::from_first_line;
+        assert_eq!(lang("#!/usr/bin/python3.12"), Some(Language::Python));
+        assert_eq!(lang("#!/bin/sh -e"), Some(Language::Shell));
+        assert_eq!(lang("#!/usr/bin/env ruby"), Some(Language::Ruby));
+        assert_eq!(lang("#!/usr/bin/env ts-node"), Some(Language::Typescript));
+        assert_eq!(lang("#!/usr/bin/awk -f"), Some(Language::Awk));
+        assert_eq!(lang("#!/usr/bin/unknown"), None);
+    }
+
+    #[test]
+    fn test_modelines() {
+        let lang = Language::from_first_line;
+        assert_eq!(lang("# vim: set ft=python:"), Some(Language::Python));
+        assert_eq!(lang("// vim: filetype=cpp"), Some(Language::Cpp));
+        assert_eq!(lang("# vi: ts=4 syntax=sh"), Some(Language::Shell));
+        assert_eq!(
+            lang(";; -*- mode: emacs-lisp; lexical-binding: t -*-"),
+            Some(Language::Elisp)
+        );
+        assert_eq!(lang("# -*- ruby -*-"), Some(Language::Ruby));
+        assert_eq!(lang("# -*- coding: utf-8 -*-"), None);
+        assert_eq!(lang("index: ft=python"), None);
+        assert_eq!(lang("hello world"), None);
+    }
+
+    #[test]
+    fn test_aliases() {
+        for variant in Language::all_variants() {
+            for alias in variant.aliases() {
+                assert_eq!(Language::from_str(alias), Ok(*variant));
+                assert!(Language::all_valid_strings().contains(alias));
+            }
+        }
+        assert_eq!(Language::from_str("Python"), Ok(Language::Python));
+        assert!(Language::from_str("cobol").is_err());
+    }
+}
@@ -0,0 +1,308 @@
+//! Line-ending, whitespace and line-length checks, like `eol == crlf`,
+//! `trailing_whitespace` and `lines > 1000`, gathered in the same pass as
+//! content patterns
+//!
+//! Lines end at `\n`; the `\r` of a CRLF isn't part of the line. Lengths are
+//! in characters.
+
+use super::encoding::TextEncoding;
+use super::{EnumMatcher, EnumPredicate, NumberMatcher};
+
+/// Line endings used by a file, for `eol in [crlf, mixed]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    Cr,
+    /// More than one kind
+    Mixed,
+    /// No line endings at all
+    None,
+}
+
+impl LineEnding {
+    fn all_variants() -> &'static [Self] {
+        &[Self::Lf, Self::Crlf, Self::Cr, Self::Mixed, Self::None]
+    }
+}
+
+impl EnumPredicate for LineEnding {
+    fn from_str(s: &str) -> Result<Self, String> {
+        let s_lower = s.to_lowercase();
+        for variant in Self::all_variants() {
+            if variant.aliases().contains(&s_lower.as_str()) {
+                return Ok(*variant);
+            }
+        }
+        Err(format!(
+            "Unknown line ending '{}'. Valid line endings: {}",
+            s,
+            Self::all_valid_strings().join(", ")
+        ))
+    }
+
+    fn all_valid_strings() -> &'static [&'static str] {
+        &[
+            "lf", "unix", "crlf", "dos", "windows", "cr", "mac", "mixed", "none",
+        ]
+    }
+
+    fn as_str(&self) -> &'static str {
+        self.aliases()[0]
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            LineEnding::Lf => &["lf", "unix"],
+            LineEnding::Crlf => &["crlf", "dos", "windows"],
+            LineEnding::Cr => &["cr", "mac"],
+            LineEnding::Mixed => &["mixed"],
+            LineEnding::None => &["none"],
+        }
+    }
+}
+
+/// A check on the lines of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineCheck {
+    /// `eol in [crlf, mixed]`
+    Eol(EnumMatcher<LineEnding>),
+    /// `no_final_newline`: a non-empty file whose last line isn't terminated
+    NoFinalNewline,
+    /// `trailing_whitespace`: a line ends in a space or tab
+    TrailingWhitespace,
+    /// `tabs_indent`: a line's indentation contains a tab
+    TabsIndent,
+    /// `lines > 1000`, counting a last line without a line ending
+    Lines(NumberMatcher),
+    /// `max_line_length > 200`
+    MaxLineLength(NumberMatcher),
+    /// `avg_line_length > 100`, rounded down
+    AvgLineLength(NumberMatcher),
+}
+
+/// A line check on the contents decoded from `encoding`, as for content
+/// patterns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinesPredicate {
+    pub check: LineCheck,
+    pub encoding: Option<TextEncoding>,
+}
+
+impl LinesPredicate {
+    pub fn new(check: LineCheck) -> Self {
+        Self {
+            check,
+            encoding: None,
+        }
+    }
+
+    /// Decode the contents from `encoding` instead of sniffing for a BOM
+    pub fn with_encoding(mut self, encoding: Option<TextEncoding>) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Decide the check from the lines seen so far, if possible before the
+    /// end of the file
+    pub fn eval_partial(&self, stats: &LineStats) -> Option<bool> {
+        match &self.check {
+            LineCheck::TrailingWhitespace if stats.trailing_whitespace => Some(true),
+            LineCheck::TabsIndent if stats.tabs_indent => Some(true),
+            LineCheck::Lines(matcher) => matcher.decide_at_least(stats.lines()),
+            LineCheck::MaxLineLength(matcher) => {
+                matcher.decide_at_least(stats.max_len.max(stats.line_len))
+            }
+            _ => None,
+        }
+    }
+
+    /// Decide the check once the whole file has been seen
+    pub fn eval(&self, stats: &LineStats) -> bool {
+        match &self.check {
+            LineCheck::Eol(matcher) => matcher.is_match(&stats.line_ending()),
+            LineCheck::NoFinalNewline => stats.last_byte.is_some_and(|b| b != b'\n'),
+            LineCheck::TrailingWhitespace => stats.trailing_whitespace,
+            LineCheck::TabsIndent => stats.tabs_indent,
+            LineCheck::Lines(matcher) => matcher.is_match(stats.lines()),
+            LineCheck::MaxLineLength(matcher) => matcher.is_match(stats.max_len),
+            LineCheck::AvgLineLength(matcher) => {
+                matcher.is_match(stats.total_len.checked_div(stats.lines()).unwrap_or(0))
+            }
+        }
+    }
+}
+
+/// Statistics gathered over the lines of a file, one chunk at a time
+#[derive(Debug, Clone, Default)]
+pub struct LineStats {
+    lf: u64,
+    crlf: u64,
+    cr: u64,
+    trailing_whitespace: bool,
+    tabs_indent: bool,
+    last_byte: Option<u8>,
+    /// A `\r` that may start a CRLF, decided by the next byte
+    pending_cr: bool,
+    /// Past the current line's leading whitespace
+    past_indent: bool,
+    /// The current line so far ends in a space or tab
+    last_blank: bool,
+    /// Terminated lines
+    terminated: u64,
+    /// The current line has started
+    in_line: bool,
+    /// Characters in the current line so far
+    line_len: u64,
+    max_len: u64,
+    total_len: u64,
+}
+
+impl LineStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            if byte == b'\n' {
+                if std::mem::take(&mut self.pending_cr) {
+                    self.crlf += 1;
+                } else {
+                    self.lf += 1;
+                }
+                self.end_line();
+            } else {
+                if std::mem::take(&mut self.pending_cr) {
+                    // A lone `\r` ends a line for `eol`, but stays in the line
+                    self.cr += 1;
+                    self.push(b'\r');
+                }
+                if byte == b'\r' {
+                    self.pending_cr = true;
+                } else {
+                    self.push(byte);
+                }
+            }
+            self.last_byte = Some(byte);
+        }
+    }
+
+    /// Account for a trailing `\r` and the last line, once the whole file has
+    /// been seen
+    pub fn finish(&mut self) {
+        if std::mem::take(&mut self.pending_cr) {
+            self.cr += 1;
+            self.push(b'\r');
+        }
+        if self.in_line {
+            self.end_line();
+        }
+    }
+
+    /// Lines so far, including one without a line ending yet
+    pub fn lines(&self) -> u64 {
+        self.terminated + u64::from(self.in_line)
+    }
+
+    pub fn line_ending(&self) -> LineEnding {
+        match (self.lf > 0, self.crlf > 0, self.cr > 0) {
+            (false, false, false) => LineEnding::None,
+            (true, false, false) => LineEnding::Lf,
+            (false, true, false) => LineEnding::Crlf,
+            (false, false, true) => LineEnding::Cr,
+            _ => LineEnding::Mixed,
+        }
+    }
+
+    fn push(&mut self, byte: u8) {
+        let blank = byte == b' ' || byte == b'\t';
+        if !self.past_indent {
+            if byte == b'\t' {
+                self.tabs_indent = true;
+            }
+            self.past_indent = !blank;
+        }
+        self.last_blank = blank;
+        self.in_line = true;
+        // Count characters, not UTF-8 continuation bytes
+        if byte & 0xc0 != 0x80 {
+            self.line_len += 1;
+        }
+    }
+
+    fn end_line(&mut self) {
+        if self.last_blank {
+            self.trailing_whitespace = true;
+        }
+        self.terminated += 1;
+        self.max_len = self.max_len.max(self.line_len);
+        self.total_len += self.line_len;
+        self.past_indent = false;
+        self.last_blank = false;
+        self.in_line = false;
+        self.line_len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predicate::Bound;
+
+    fn stats(chunks: &[&[u8]]) -> LineStats {
+        let mut stats = LineStats::new();
+        for chunk in chunks {
+            stats.update(chunk);
+        }
+        stats.finish();
+        stats
+    }
+
+    #[test]
+    fn test_line_endings() {
+        assert_eq!(stats(&[b"a\nb\n"]).line_ending(), LineEnding::Lf);
+        // A CRLF split across chunks is still one CRLF
+        assert_eq!(stats(&[b"a\r", b"\nb\r\n"]).line_ending(), LineEnding::Crlf);
+        assert_eq!(stats(&[b"a\rb\r"]).line_ending(), LineEnding::Cr);
+        assert_eq!(stats(&[b"a\r\nb\n"]).line_ending(), LineEnding::Mixed);
+        assert_eq!(stats(&[b"abc"]).line_ending(), LineEnding::None);
+    }
+
+    #[test]
+    fn test_whitespace() {
+        assert!(stats(&[b"a \nb\n"]).trailing_whitespace);
+        assert!(stats(&[b"a\nb\t"]).trailing_whitespace);
+        assert!(!stats(&[b"a\r\nb\r\n"]).trailing_whitespace);
+        assert!(stats(&[b"  \tx\n"]).tabs_indent);
+        assert!(!stats(&[b"x\ty\n    z\n"]).tabs_indent);
+    }
+
+    #[test]
+    fn test_line_lengths() {
+        let s = stats(&[b"ab\n", "caf\u{e9}\n".as_bytes(), b"\nxyzzy"]);
+        assert_eq!(s.lines(), 4);
+        assert_eq!(s.max_len, 5);
+        assert_eq!(s.total_len, 11);
+        assert_eq!(stats(&[b""]).lines(), 0);
+        assert_eq!(stats(&[b"\n"]).lines(), 1);
+    }
+
+    #[test]
+    fn test_decide_at_least() {
+        let more_than_two = NumberMatcher::In(Bound::Left(3..));
+        assert_eq!(more_than_two.decide_at_least(2), None);
+        assert_eq!(more_than_two.decide_at_least(3), Some(true));
+        let under_two = NumberMatcher::In(Bound::Right(..2));
+        assert_eq!(under_two.decide_at_least(2), Some(false));
+        assert_eq!(NumberMatcher::Equals(1).decide_at_least(1), None);
+    }
+
+    #[test]
+    fn test_final_newline() {
+        let check = LinesPredicate::new(LineCheck::NoFinalNewline);
+        assert!(check.eval(&stats(&[b"a\nb"])));
+        assert!(!check.eval(&stats(&[b"a\nb\n"])));
+        assert!(!check.eval(&stats(&[b""])));
+    }
+}
@@ -0,0 +1,165 @@
+//! MIME type detection from magic numbers, for `mime == "image/png"`
+//!
+//! Only the first block of the file is looked at, so a `.txt` that's really a
+//! zip is reported as `application/zip`. Names follow `file --mime-type`.
+
+use super::header::is_binary;
+
+/// Fixed byte signatures: offset, magic bytes, MIME type
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    // Images
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (0, b"II*\0", "image/tiff"),
+    (0, b"MM\0*", "image/tiff"),
+    (0, b"\0\0\x01\0", "image/vnd.microsoft.icon"),
+    (0, b"8BPS", "image/vnd.adobe.photoshop"),
+    // Audio and video
+    (0, b"ID3", "audio/mpeg"),
+    (0, b"OggS", "audio/ogg"),
+    (0, b"fLaC", "audio/flac"),
+    (0, b"\x1a\x45\xdf\xa3", "video/x-matroska"),
+    (4, b"ftyp", "video/mp4"),
+    // Documents
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"%!PS", "application/postscript"),
+    (0, b"{\\rtf", "text/rtf"),
+    // Archives and compression
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"PK\x05\x06", "application/zip"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"BZh", "application/x-bzip2"),
+    (0, b"\xfd7zXZ\0", "application/x-xz"),
+    (0, b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (0, b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (0, b"Rar!\x1a\x07", "application/vnd.rar"),
+    (257, b"ustar", "application/x-tar"),
+    // Executables and bytecode
+    (0, b"MZ", "application/x-dosexec"),
+    (0, b"\xfe\xed\xfa\xce", "application/x-mach-binary"),
+    (0, b"\xfe\xed\xfa\xcf", "application/x-mach-binary"),
+    (0, b"\xce\xfa\xed\xfe", "application/x-mach-binary"),
+    (0, b"\xcf\xfa\xed\xfe", "application/x-mach-binary"),
+    (0, b"\xca\xfe\xba\xbe", "application/x-java-applet"),
+    (0, b"\0asm", "application/wasm"),
+    // Data
+    (0, b"SQLite format 3\0", "application/vnd.sqlite3"),
+    (0, b"wOFF", "font/woff"),
+    (0, b"wOF2", "font/woff2"),
+];
+
+/// RIFF containers, told apart by the form type at offset 8
+const RIFF_FORMS: &[(&[u8], &str)] = &[
+    (b"WEBP", "image/webp"),
+    (b"WAVE", "audio/x-wav"),
+    (b"AVI ", "video/x-msvideo"),
+];
+
+/// Detect the MIME type of a file from its first block
+///
+/// Files without a known signature are `text/plain` or
+/// `application/octet-stream`, following the `binary` heuristic, and empty
+/// files are `application/x-empty`.
+pub fn detect_mime(header: &[u8]) -> &'static str {
+    if header.is_empty() {
+        return "application/x-empty";
+    }
+    if header.starts_with(b"\x7fELF") {
+        return elf_mime(header);
+    }
+    if header.starts_with(b"RIFF") {
+        if let Some(form) = header.get(8..12) {
+            if let Some((_, mime)) = RIFF_FORMS.iter().find(|(f, _)| *f == form) {
+                return mime;
+            }
+        }
+    }
+    if let Some((_, _, mime)) = SIGNATURES
+        .iter()
+        .find(|(offset, magic, _)| header.get(*offset..).is_some_and(|h| h.starts_with(magic)))
+    {
+        return mime;
+    }
+
+    if is_binary(header) {
+        return "application/octet-stream";
+    }
+    markup_mime(header).unwrap_or("text/plain")
+}
+
+/// ELF files by object type (`e_type`), read in the file's byte order
+fn elf_mime(header: &[u8]) -> &'static str {
+    let e_type = match (header.get(5), header.get(16..18)) {
+        (Some(1), Some(&[a, b])) => u16::from_le_bytes([a, b]),
+        (Some(2), Some(&[a, b])) => u16::from_be_bytes([a, b]),
+        _ => return "application/octet-stream",
+    };
+    match e_type {
+        1 => "application/x-object",
+        2 => "application/x-executable",
+        3 => "application/x-sharedlib",
+        4 => "application/x-coredump",
+        _ => "application/octet-stream",
+    }
+}
+
+/// XML and HTML documents, recognized by their leading tag
+fn markup_mime(header: &[u8]) -> Option<&'static str> {
+    let start = header.iter().position(|b| !b.is_ascii_whitespace())?;
+    let head = &header[start..header.len().min(start + 16)];
+    let head = head.to_ascii_lowercase();
+    if head.starts_with(b"<?xml") {
+        Some("text/xml")
+    } else if head.starts_with(b"<!doctype html") || head.starts_with(b"<html") {
+        Some("text/html")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A little-endian ELF header with the given object type
+    fn elf(e_type: u16) -> Vec<u8> {
+        let mut header = b"\x7fELF\x02\x01\x01".to_vec();
+        header.resize(16, 0);
+        header.extend(e_type.to_le_bytes());
+        header.resize(64, 0);
+        header
+    }
+
+    #[test]
+    fn test_signatures() {
+        assert_eq!(detect_mime(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), "image/png");
+        assert_eq!(detect_mime(b"PK\x03\x04\x14\0\0\0"), "application/zip");
+        assert_eq!(detect_mime(b"%PDF-1.7\n"), "application/pdf");
+        assert_eq!(detect_mime(b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+        assert_eq!(detect_mime(b"\0\0\0\x18ftypmp42"), "video/mp4");
+
+        let mut tar = vec![0u8; 512];
+        tar[..9].copy_from_slice(b"hello.txt");
+        tar[257..262].copy_from_slice(b"ustar");
+        assert_eq!(detect_mime(&tar), "application/x-tar");
+    }
+
+    #[test]
+    fn test_elf() {
+        assert_eq!(detect_mime(&elf(1)), "application/x-object");
+        assert_eq!(detect_mime(&elf(2)), "application/x-executable");
+        assert_eq!(detect_mime(&elf(3)), "application/x-sharedlib");
+        assert_eq!(detect_mime(b"\x7fELF"), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_fallbacks() {
+        assert_eq!(detect_mime(b""), "application/x-empty");
+        assert_eq!(detect_mime(b"hello\n"), "text/plain");
+        assert_eq!(detect_mime(b"hello\0world"), "application/octet-stream");
+        assert_eq!(detect_mime(b"  <!DOCTYPE HTML>\n"), "text/html");
+        assert_eq!(detect_mime(b"<?xml version=\"1.0\"?>\n<a/>"), "text/xml");
+    }
+}
@@ -1,6 +1,30 @@
+mod encoding;
 mod enum_matcher;
-
+mod ftype;
+mod hash;
+mod header;
+mod lang;
+mod lines;
+mod mime;
+mod mounts;
+mod ownership;
+mod permissions;
+mod tree;
+mod xattr;
+
+pub use encoding::{ContentDecoder, TextEncoding};
 pub use enum_matcher::{EnumMatcher, EnumPredicate};
+pub use ftype::{FileTypeDefs, FileTypeMatcher};
+pub use hash::{hash_file, hash_reader, Digest, HashAlgorithm, HashPredicate, Hasher};
+pub use header::{is_binary, HeaderPredicate, HEADER_SIZE};
+pub use lang::Language;
+pub use lines::{LineCheck, LineEnding, LineStats, LinesPredicate};
+pub use mime::detect_mime;
+pub use mounts::{fstype_of, is_mount_point, FsType};
+pub use ownership::{group_exists, group_id, user_exists, user_id, IdMatcher};
+pub use permissions::{has_access, permission_bits, symbolic_mode, Access};
+pub use tree::{TreePredicate, TreeRelation, TreeTotals};
+pub use xattr::{InodeFlag, XattrPredicate, SELINUX_XATTR};
 
 use regex_automata::dfa::dense::DFA;
 use std::collections::HashSet;
@@ -14,9 +38,11 @@ use std::{
     fs::Metadata,
     ops::Range,
     path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use crate::expr::short_circuit::ShortCircuit;
+use crate::parser::typed::{TemporalOperator, TemporalSelector};
 use crate::predicate_error::PredicateParseError;
 use crate::util::Done;
 use chrono::{DateTime, Local};
@@ -225,6 +251,7 @@ pub enum NumberMatcher {
     In(Bound),
     Equals(u64),
     NotEquals(u64),
+    AnyBits(u64), // any of the masked bits set, e.g. `mode & 0o002`
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -235,14 +262,33 @@ pub enum TimeMatcher {
     AfterOrEqual(DateTime<Local>),
     Equals(DateTime<Local>),
     NotEquals(DateTime<Local>),
+    /// Compare against another timestamp of the same entity, e.g. `modified > created`
+    Selector(TemporalOperator, TemporalSelector),
 }
 
 impl TimeMatcher {
-    pub fn is_match(&self, timestamp: i64) -> bool {
-        use std::time::UNIX_EPOCH;
+    /// Match a timestamp, resolving selector-valued right-hand sides
+    /// against the same entity's metadata
+    pub fn is_match_metadata(&self, timestamp: SystemTime, metadata: &Metadata) -> bool {
+        match self {
+            TimeMatcher::Selector(op, selector) => {
+                // Both sides come from the same stat call
+                let other = timestamp_of(*selector, metadata);
+                match op {
+                    TemporalOperator::Before => timestamp < other,
+                    TemporalOperator::After => timestamp > other,
+                    TemporalOperator::BeforeOrEqual => timestamp <= other,
+                    TemporalOperator::AfterOrEqual => timestamp >= other,
+                    TemporalOperator::Equals => timestamp == other,
+                    TemporalOperator::NotEquals => timestamp != other,
+                }
+            }
+            _ => self.is_match(timestamp),
+        }
+    }
 
-        let file_time = UNIX_EPOCH + std::time::Duration::from_secs(timestamp as u64);
-        let file_datetime: DateTime<Local> = file_time.into();
+    pub fn is_match(&self, timestamp: SystemTime) -> bool {
+        let file_datetime: DateTime<Local> = timestamp.into();
 
         match self {
             TimeMatcher::Before(dt) => file_datetime < *dt,
@@ -251,16 +297,57 @@ impl TimeMatcher {
             TimeMatcher::AfterOrEqual(dt) => file_datetime >= *dt,
             TimeMatcher::Equals(dt) => file_datetime.date_naive() == dt.date_naive(),
             TimeMatcher::NotEquals(dt) => file_datetime.date_naive() != dt.date_naive(),
+            // Needs the entity's metadata to resolve, see `is_match_metadata`
+            TimeMatcher::Selector(..) => false,
         }
     }
 }
 
+/// Bytes allocated on disk; `st_blocks` is always in 512-byte units
+pub fn disk_usage(metadata: &Metadata) -> u64 {
+    metadata.blocks() * 512
+}
+
+/// Timestamp for a temporal selector, keeping the sub-second part so
+/// `modified > mtime_of(ref)` works like find's `-newer`
+pub fn timestamp_of(selector: TemporalSelector, metadata: &Metadata) -> SystemTime {
+    let (secs, nanos) = match selector {
+        TemporalSelector::Modified => (metadata.mtime(), metadata.mtime_nsec()),
+        TemporalSelector::Created => (metadata.ctime(), metadata.ctime_nsec()),
+        TemporalSelector::Accessed => (metadata.atime(), metadata.atime_nsec()),
+        TemporalSelector::TreeNewestMtime => {
+            unreachable!("the typechecker doesn't accept tree aggregates on the right-hand side")
+        }
+    };
+    let whole = Duration::from_secs(secs.unsigned_abs());
+    let time = if secs < 0 {
+        UNIX_EPOCH - whole
+    } else {
+        UNIX_EPOCH + whole
+    };
+    time + Duration::from_nanos(nanos as u64)
+}
+
 impl NumberMatcher {
     pub fn is_match(&self, x: u64) -> bool {
         match self {
             NumberMatcher::In(b) => b.contains(&x),
             NumberMatcher::Equals(cmp) => x == *cmp,
             NumberMatcher::NotEquals(cmp) => x != *cmp,
+            NumberMatcher::AnyBits(mask) => x & mask != 0,
+        }
+    }
+
+    /// Decide the match for a count that's at least `so_far` and may still
+    /// grow, if more of it can't change the answer
+    pub fn decide_at_least(&self, so_far: u64) -> Option<bool> {
+        match self {
+            NumberMatcher::In(Bound::Left(range)) if range.contains(&so_far) => Some(true),
+            NumberMatcher::In(Bound::Right(range)) if !range.contains(&so_far) => Some(false),
+            NumberMatcher::In(Bound::Full(range)) if so_far >= range.end => Some(false),
+            NumberMatcher::Equals(n) if so_far > *n => Some(false),
+            NumberMatcher::NotEquals(n) if so_far > *n => Some(true),
+            _ => None,
         }
     }
 }
@@ -432,7 +519,7 @@ pub enum NumericalOp {
 pub enum Predicate<
     Name = NamePredicate,
     Metadata = MetadataPredicate,
-    Content = StreamingCompiledContentPredicate,
+    Content = ContentPredicate,
     Structured = StructuredDataPredicate,
 > {
     Name(Arc<Name>),
@@ -448,8 +535,8 @@ impl<N, M, C, S> Predicate<N, M, C, S> {
     pub fn meta(m: M) -> Self {
         Self::Metadata(Arc::new(m))
     }
-    pub fn contents(c: C) -> Self {
-        Self::Content(c)
+    pub fn contents(c: impl Into<C>) -> Self {
+        Self::Content(c.into())
     }
     pub fn structured(s: S) -> Self {
         Self::Structured(s)
@@ -494,12 +581,28 @@ impl<A, B, S> Predicate<NamePredicate, A, B, S> {
 }
 
 impl<A, B, S> Predicate<A, MetadataPredicate, B, S> {
+    /// Evaluate metadata predicates that only look at this entry, leaving
+    /// tree predicates for a later async pass
+    pub fn eval_local_metadata_predicate(
+        self,
+        path: &Path,
+        metadata: &Metadata,
+    ) -> ShortCircuit<Predicate<A, MetadataPredicate, B, S>> {
+        match self {
+            Predicate::Metadata(p) if !p.is_tree() => {
+                ShortCircuit::Known(p.is_match(path, metadata))
+            }
+            p => ShortCircuit::Unknown(p),
+        }
+    }
+
     pub fn eval_metadata_predicate(
         self,
+        path: &Path,
         metadata: &Metadata,
     ) -> ShortCircuit<Predicate<A, Done, B, S>> {
         match self {
-            Predicate::Metadata(p) => ShortCircuit::Known(p.is_match(metadata)),
+            Predicate::Metadata(p) => ShortCircuit::Known(p.is_match(path, metadata)),
             Predicate::Content(x) => ShortCircuit::Unknown(Predicate::Content(x)),
             Predicate::Name(x) => ShortCircuit::Unknown(Predicate::Name(x)),
             Predicate::Structured(x) => ShortCircuit::Unknown(Predicate::Structured(x)),
@@ -509,13 +612,14 @@ impl<A, B, S> Predicate<A, MetadataPredicate, B, S> {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum NamePredicate {
-    BaseName(StringMatcher),  // filename without extension
-    FileName(StringMatcher),  // complete filename with extension
-    DirPath(StringMatcher),   // directory path only
-    FullPath(StringMatcher),  // complete path including filename
-    Extension(StringMatcher), // file extension
-    ParentDir(StringMatcher), // immediate parent directory name
-    Depth(NumberMatcher),     // directory depth from base path
+    BaseName(StringMatcher),   // filename without extension
+    FileName(StringMatcher),   // complete filename with extension
+    DirPath(StringMatcher),    // directory path only
+    FullPath(StringMatcher),   // complete path including filename
+    Extension(StringMatcher),  // file extension
+    ParentDir(StringMatcher),  // immediate parent directory name
+    Depth(NumberMatcher),      // directory depth from base path
+    FileType(FileTypeMatcher), // named glob set, e.g. ftype == rust
 }
 
 impl NamePredicate {
@@ -624,6 +728,7 @@ impl NamePredicate {
                 };
                 matcher.is_match(depth)
             }
+            NamePredicate::FileType(matcher) => matcher.is_match(path),
         }
     }
 }
@@ -649,20 +754,49 @@ impl Bound {
 #[derive(Debug, PartialEq, Eq)]
 pub enum MetadataPredicate {
     Filesize(NumberMatcher),
+    DiskUsage(NumberMatcher),          // allocated bytes, st_blocks * 512
+    Blocks(NumberMatcher),             // allocated 512-byte blocks
+    Sparse,                            // allocated size well below apparent size
     Type(EnumMatcher<DetectFileType>), // file type with parse-time validation
     Modified(TimeMatcher),
     Created(TimeMatcher),
     Accessed(TimeMatcher),
+    Mode(NumberMatcher),        // permission bits, including setuid/setgid/sticky
+    Permissions(StringMatcher), // symbolic permissions, e.g. "rwxr-x---"
+    Access(Access),             // current user may read/write
+    Uid(NumberMatcher),
+    Gid(NumberMatcher),
+    User(IdMatcher),  // owner, resolved from user names at typecheck
+    Group(IdMatcher), // group, resolved from group names at typecheck
+    Orphaned,         // uid or gid has no passwd/group entry
+    Inode(NumberMatcher),
+    Nlink(NumberMatcher),
+    Device(NumberMatcher),
+    Hardlinked, // non-directory with more than one link
+    FsType(EnumMatcher<FsType>),
+    Mountpoint, // directory listed in mountinfo
+    Xattr(XattrPredicate),
+    Empty,                        // zero-length file or directory without entries
+    Entries(NumberMatcher),       // number of directory entries
+    Tree(TreePredicate),          // sub-expression over other entries, see `eval::tree`
+    TreeSize(NumberMatcher),      // total size of files below a directory
+    TreeFiles(NumberMatcher),     // number of files below a directory
+    TreeNewestMtime(TimeMatcher), // newest mtime of a directory and everything below
 }
 
 impl MetadataPredicate {
-    pub fn is_match(&self, metadata: &Metadata) -> bool {
-        self.is_match_with_path(metadata)
-    }
-
-    pub fn is_match_with_path(&self, metadata: &Metadata) -> bool {
+    pub fn is_match(&self, path: &Path, metadata: &Metadata) -> bool {
         match self {
             MetadataPredicate::Filesize(range) => range.is_match(metadata.size()),
+            MetadataPredicate::DiskUsage(range) => range.is_match(disk_usage(metadata)),
+            MetadataPredicate::Blocks(range) => range.is_match(metadata.blocks()),
+            MetadataPredicate::Sparse => {
+                // Larger than one block and less than half allocated; smaller files
+                // may have their data stored inline with zero blocks
+                metadata.is_file()
+                    && metadata.size() > metadata.blksize()
+                    && disk_usage(metadata) < metadata.size() / 2
+            }
             MetadataPredicate::Type(enum_matcher) => {
                 let ft: FileType = metadata.file_type();
                 if let Some(detect_type) = DetectFileType::from_fs_type(&ft) {
@@ -671,11 +805,85 @@ impl MetadataPredicate {
                     false
                 }
             }
-            MetadataPredicate::Modified(matcher) => matcher.is_match(metadata.mtime()),
-            MetadataPredicate::Created(matcher) => matcher.is_match(metadata.ctime()),
-            MetadataPredicate::Accessed(matcher) => matcher.is_match(metadata.atime()),
+            MetadataPredicate::Modified(matcher) => matcher
+                .is_match_metadata(timestamp_of(TemporalSelector::Modified, metadata), metadata),
+            MetadataPredicate::Created(matcher) => matcher
+                .is_match_metadata(timestamp_of(TemporalSelector::Created, metadata), metadata),
+            MetadataPredicate::Accessed(matcher) => matcher
+                .is_match_metadata(timestamp_of(TemporalSelector::Accessed, metadata), metadata),
+            MetadataPredicate::Mode(matcher) => matcher.is_match(permission_bits(metadata)),
+            MetadataPredicate::Permissions(matcher) => {
+                matcher.is_match(&symbolic_mode(permission_bits(metadata)))
+            }
+            MetadataPredicate::Access(access) => has_access(metadata, *access),
+            MetadataPredicate::Uid(matcher) => matcher.is_match(u64::from(metadata.uid())),
+            MetadataPredicate::Gid(matcher) => matcher.is_match(u64::from(metadata.gid())),
+            MetadataPredicate::User(matcher) => matcher.is_match(metadata.uid()),
+            MetadataPredicate::Group(matcher) => matcher.is_match(metadata.gid()),
+            MetadataPredicate::Orphaned => {
+                !user_exists(metadata.uid()) || !group_exists(metadata.gid())
+            }
+            MetadataPredicate::Inode(matcher) => matcher.is_match(metadata.ino()),
+            MetadataPredicate::Nlink(matcher) => matcher.is_match(metadata.nlink()),
+            MetadataPredicate::Device(matcher) => matcher.is_match(metadata.dev()),
+            // Directories always have several links ('.', '..' of children)
+            MetadataPredicate::Hardlinked => !metadata.is_dir() && metadata.nlink() > 1,
+            MetadataPredicate::FsType(matcher) => {
+                fstype_of(metadata.dev()).is_some_and(|fstype| matcher.is_match(&fstype))
+            }
+            MetadataPredicate::Mountpoint => metadata.is_dir() && is_mount_point(path),
+            MetadataPredicate::Xattr(predicate) => predicate.is_match(path),
+            MetadataPredicate::Empty => {
+                if metadata.is_dir() {
+                    std::fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_none())
+                } else {
+                    metadata.is_file() && metadata.size() == 0
+                }
+            }
+            MetadataPredicate::Entries(matcher) => {
+                metadata.is_dir()
+                    && std::fs::read_dir(path)
+                        .is_ok_and(|entries| matcher.is_match(entries.count() as u64))
+            }
+            MetadataPredicate::Tree(_) => {
+                unreachable!("tree predicates are evaluated asynchronously by eval::fs")
+            }
+            MetadataPredicate::TreeSize(_)
+            | MetadataPredicate::TreeFiles(_)
+            | MetadataPredicate::TreeNewestMtime(_) => {
+                unreachable!("tree aggregates are resolved by the walk in parse_and_run_fs")
+            }
         }
     }
+
+    /// Whether this predicate needs totals over a directory's tree, which
+    /// are only known once the walk has visited everything below it
+    pub fn is_aggregate(&self) -> bool {
+        matches!(
+            self,
+            MetadataPredicate::TreeSize(_)
+                | MetadataPredicate::TreeFiles(_)
+                | MetadataPredicate::TreeNewestMtime(_)
+        )
+    }
+
+    /// Match an aggregate predicate against a directory's totals
+    pub fn is_match_totals(&self, totals: &TreeTotals, metadata: &Metadata) -> bool {
+        match self {
+            MetadataPredicate::TreeSize(matcher) => matcher.is_match(totals.size),
+            MetadataPredicate::TreeFiles(matcher) => matcher.is_match(totals.files),
+            MetadataPredicate::TreeNewestMtime(matcher) => {
+                matcher.is_match_metadata(totals.newest_mtime, metadata)
+            }
+            _ => unreachable!("only aggregate predicates have totals"),
+        }
+    }
+
+    /// Whether this predicate needs to evaluate other entries, which can't
+    /// happen in the synchronous metadata pass
+    pub fn is_tree(&self) -> bool {
+        matches!(self, MetadataPredicate::Tree(_))
+    }
 }
 
 /// Structured data predicate for yaml/json/toml queries
@@ -728,10 +936,133 @@ pub enum StructuredDataPredicate {
     },
 }
 
+/// Predicates on a file's contents, all evaluated from a single read
+#[derive(Debug, PartialEq)]
+pub enum ContentPredicate {
+    /// `content ~= ...`, `content contains ...`
+    Regex(StreamingCompiledContentPredicate),
+    /// `sha256 == ...`, `blake3 in [...]`, `content_eq(...)`
+    Hash(HashPredicate),
+    /// `text`, `binary`, `mime`, `lang`, `shebang`, `interpreter`: decided
+    /// from the first block, before any full read
+    Header(HeaderPredicate),
+    /// `eol == crlf`, `trailing_whitespace`, ...: gathered over the lines in
+    /// the same read as content patterns
+    Lines(LinesPredicate),
+    /// `count(content contains TODO) > 5`
+    Count(CountPredicate),
+    /// `head(20) contains Copyright`, `lines[1..3] contains SPDX`
+    Window(WindowPredicate),
+    /// `near("unsafe", "// SAFETY:", 3 lines)`
+    Near(NearPredicate),
+}
+
+impl ContentPredicate {
+    pub(crate) fn as_ref(&self) -> ContentPredicateRef<'_> {
+        match self {
+            Self::Regex(r) => ContentPredicateRef::Regex(r.as_ref()),
+            Self::Hash(h) => ContentPredicateRef::Hash(h),
+            Self::Header(h) => ContentPredicateRef::Header(h),
+            Self::Lines(l) => ContentPredicateRef::Lines(l),
+            Self::Count(c) => ContentPredicateRef::Count(c.pattern.as_ref(), &c.matcher),
+            Self::Window(w) => ContentPredicateRef::Window(w.pattern.as_ref(), w.window),
+            Self::Near(n) => {
+                ContentPredicateRef::Near(n.first.as_ref(), n.second.as_ref(), n.distance)
+            }
+        }
+    }
+}
+
+impl From<StreamingCompiledContentPredicate> for ContentPredicate {
+    fn from(value: StreamingCompiledContentPredicate) -> Self {
+        Self::Regex(value)
+    }
+}
+
+impl From<HashPredicate> for ContentPredicate {
+    fn from(value: HashPredicate) -> Self {
+        Self::Hash(value)
+    }
+}
+
+impl From<HeaderPredicate> for ContentPredicate {
+    fn from(value: HeaderPredicate) -> Self {
+        Self::Header(value)
+    }
+}
+
+impl From<LinesPredicate> for ContentPredicate {
+    fn from(value: LinesPredicate) -> Self {
+        Self::Lines(value)
+    }
+}
+
+impl From<CountPredicate> for ContentPredicate {
+    fn from(value: CountPredicate) -> Self {
+        Self::Count(value)
+    }
+}
+
+impl From<WindowPredicate> for ContentPredicate {
+    fn from(value: WindowPredicate) -> Self {
+        Self::Window(value)
+    }
+}
+
+impl From<NearPredicate> for ContentPredicate {
+    fn from(value: NearPredicate) -> Self {
+        Self::Near(value)
+    }
+}
+
+/// Borrowed form of [`ContentPredicate`], sharing compiled DFAs during evaluation
+#[derive(Clone, Debug)]
+pub enum ContentPredicateRef<'a> {
+    Regex(StreamingCompiledContentPredicateRef<'a>),
+    Hash(&'a HashPredicate),
+    Header(&'a HeaderPredicate),
+    Lines(&'a LinesPredicate),
+    Count(StreamingCompiledContentPredicateRef<'a>, &'a NumberMatcher),
+    Window(StreamingCompiledContentPredicateRef<'a>, LineWindow),
+    Near(
+        StreamingCompiledContentPredicateRef<'a>,
+        StreamingCompiledContentPredicateRef<'a>,
+        u64,
+    ),
+}
+
+impl ContentPredicateRef<'_> {
+    /// Whether this is resolved by the header phase rather than a full read
+    pub fn is_header(&self) -> bool {
+        matches!(self, Self::Header(_))
+    }
+
+    /// Whether this is resolved by seeking to the end of the file rather than
+    /// a full read
+    pub fn is_tail(&self) -> bool {
+        matches!(self, Self::Window(_, LineWindow::Tail(_)))
+    }
+}
+
+/// What a content pattern runs over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PatternScope {
+    /// `content ~= ...`: the whole file as one haystack
+    #[default]
+    File,
+    /// `line ~= ...`: any single line matches
+    AnyLine,
+    /// `all_lines ~= ...`: every line matches
+    AllLines,
+}
+
 #[derive(Debug)]
 pub struct StreamingCompiledContentPredicate {
     inner: Box<DFA<Vec<u32>>>,
     source: String,
+    /// Encoding to decode the contents from, or `None` to sniff for a BOM
+    encoding: Option<TextEncoding>,
+    scope: PatternScope,
 }
 
 impl StreamingCompiledContentPredicate {
@@ -740,27 +1071,102 @@ impl StreamingCompiledContentPredicate {
             Ok(inner) => Ok(Self {
                 inner: Box::new(inner),
                 source,
+                encoding: None,
+                scope: PatternScope::File,
             }),
             Err(e) => Err(PredicateParseError::Dfa(e.to_string())),
         }
     }
 
+    /// Decode the contents from `encoding` instead of sniffing for a BOM
+    pub fn with_encoding(mut self, encoding: Option<TextEncoding>) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Run the pattern over each line rather than the whole file, so `^` and
+    /// `$` anchor at line boundaries
+    pub fn with_scope(mut self, scope: PatternScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Whether the pattern can match empty text, so its matches can't be
+    /// counted
+    pub fn can_match_empty(&self) -> bool {
+        regex_automata::util::syntax::parse(&self.source)
+            .map(|hir| hir.properties().minimum_len() == Some(0))
+            .unwrap_or(false)
+    }
+
     pub(crate) fn as_ref(&self) -> StreamingCompiledContentPredicateRef<'_> {
         StreamingCompiledContentPredicateRef {
             inner: &self.inner,
             source: &self.source,
+            encoding: self.encoding,
+            scope: self.scope,
         }
     }
 }
 
 impl PartialEq for StreamingCompiledContentPredicate {
     fn eq(&self, other: &Self) -> bool {
-        self.source == other.source
+        self.source == other.source && self.encoding == other.encoding && self.scope == other.scope
     }
 }
 
+/// `count(content contains TODO) > 5`: the number of non-overlapping matches
+/// of a content pattern
+#[derive(Debug, PartialEq)]
+pub struct CountPredicate {
+    pub pattern: StreamingCompiledContentPredicate,
+    pub matcher: NumberMatcher,
+}
+
+/// Lines a windowed content pattern runs over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineWindow {
+    /// `head(20)`: the first lines
+    Head(u64),
+    /// `tail(5)`: the last lines, a last line without a line ending included
+    Tail(u64),
+    /// `lines[1..3]`: lines numbered from 1, both ends included
+    Range(u64, u64),
+}
+
+impl LineWindow {
+    /// Zero-based indexes of the window's lines, unless it's counted from the
+    /// end
+    pub fn from_start(self) -> Option<std::ops::Range<u64>> {
+        match self {
+            LineWindow::Head(n) => Some(0..n),
+            LineWindow::Tail(_) => None,
+            LineWindow::Range(first, last) => Some(first.saturating_sub(1)..last),
+        }
+    }
+}
+
+/// `head(20) contains Copyright`: a content pattern matching any single line
+/// in a window, reading no further than it needs to
+#[derive(Debug, PartialEq)]
+pub struct WindowPredicate {
+    pub pattern: StreamingCompiledContentPredicate,
+    pub window: LineWindow,
+}
+
+/// `near("unsafe", "// SAFETY:", 3 lines)`: lines matching each pattern at
+/// most `distance` lines apart, in either order
+#[derive(Debug, PartialEq)]
+pub struct NearPredicate {
+    pub first: StreamingCompiledContentPredicate,
+    pub second: StreamingCompiledContentPredicate,
+    pub distance: u64,
+}
+
 #[derive(Clone, Debug)]
 pub struct StreamingCompiledContentPredicateRef<'a> {
     pub inner: &'a DFA<Vec<u32>>,
     pub source: &'a str,
+    pub encoding: Option<TextEncoding>,
+    pub scope: PatternScope,
 }
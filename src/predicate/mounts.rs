@@ -0,0 +1,269 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use super::EnumPredicate;
+
+const MOUNTINFO: &str = "/proc/self/mountinfo";
+
+/// Filesystem type enumeration for `fstype` predicates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FsType {
+    Ext2,
+    Ext3,
+    Ext4,
+    Xfs,
+    Btrfs,
+    Zfs,
+    Tmpfs,
+    Ramfs,
+    Overlay,
+    Squashfs,
+    Iso9660,
+    Vfat,
+    Exfat,
+    Ntfs,
+    Nfs,
+    Cifs,
+    Fuse,
+    Proc,
+    Sysfs,
+    Devtmpfs,
+    Devpts,
+    Cgroup,
+    Other, // anything not listed above
+}
+
+impl FsType {
+    /// All enum variants for iteration
+    fn all_variants() -> &'static [Self] {
+        &[
+            Self::Ext2,
+            Self::Ext3,
+            Self::Ext4,
+            Self::Xfs,
+            Self::Btrfs,
+            Self::Zfs,
+            Self::Tmpfs,
+            Self::Ramfs,
+            Self::Overlay,
+            Self::Squashfs,
+            Self::Iso9660,
+            Self::Vfat,
+            Self::Exfat,
+            Self::Ntfs,
+            Self::Nfs,
+            Self::Cifs,
+            Self::Fuse,
+            Self::Proc,
+            Self::Sysfs,
+            Self::Devtmpfs,
+            Self::Devpts,
+            Self::Cgroup,
+            Self::Other,
+        ]
+    }
+
+    /// Classify a type name as it appears in mountinfo, e.g. `nfs4` or `fuse.sshfs`
+    fn from_mountinfo(name: &str) -> Self {
+        if name == "fuse" || name.starts_with("fuse.") || name == "fuseblk" {
+            return Self::Fuse;
+        }
+        Self::from_str(name).unwrap_or(Self::Other)
+    }
+}
+
+impl EnumPredicate for FsType {
+    fn from_str(s: &str) -> Result<Self, String> {
+        let s_lower = s.to_lowercase();
+        for variant in Self::all_variants() {
+            if variant.aliases().contains(&s_lower.as_str()) {
+                return Ok(*variant);
+            }
+        }
+        Err(format!(
+            "Unknown filesystem type '{}'. Valid types: {}",
+            s,
+            Self::all_valid_strings().join(", ")
+        ))
+    }
+
+    fn all_valid_strings() -> &'static [&'static str] {
+        &[
+            "ext2",
+            "ext3",
+            "ext4",
+            "xfs",
+            "btrfs",
+            "zfs",
+            "tmpfs",
+            "ramfs",
+            "overlay",
+            "overlayfs",
+            "squashfs",
+            "iso9660",
+            "vfat",
+            "fat",
+            "exfat",
+            "ntfs",
+            "ntfs3",
+            "nfs",
+            "nfs4",
+            "cifs",
+            "smb3",
+            "fuse",
+            "proc",
+            "sysfs",
+            "devtmpfs",
+            "devpts",
+            "cgroup",
+            "cgroup2",
+            "other",
+        ]
+    }
+
+    fn as_str(&self) -> &'static str {
+        self.aliases()[0]
+    }
+
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            FsType::Ext2 => &["ext2"],
+            FsType::Ext3 => &["ext3"],
+            FsType::Ext4 => &["ext4"],
+            FsType::Xfs => &["xfs"],
+            FsType::Btrfs => &["btrfs"],
+            FsType::Zfs => &["zfs"],
+            FsType::Tmpfs => &["tmpfs"],
+            FsType::Ramfs => &["ramfs"],
+            FsType::Overlay => &["overlay", "overlayfs"],
+            FsType::Squashfs => &["squashfs"],
+            FsType::Iso9660 => &["iso9660"],
+            FsType::Vfat => &["vfat", "fat"],
+            FsType::Exfat => &["exfat"],
+            FsType::Ntfs => &["ntfs", "ntfs3"],
+            FsType::Nfs => &["nfs", "nfs4"],
+            FsType::Cifs => &["cifs", "smb3"],
+            FsType::Fuse => &["fuse"],
+            FsType::Proc => &["proc"],
+            FsType::Sysfs => &["sysfs"],
+            FsType::Devtmpfs => &["devtmpfs"],
+            FsType::Devpts => &["devpts"],
+            FsType::Cgroup => &["cgroup", "cgroup2"],
+            FsType::Other => &["other"],
+        }
+    }
+}
+
+/// One line of `/proc/self/mountinfo`
+struct MountEntry {
+    device: u64,
+    mount_point: PathBuf,
+    fstype: FsType,
+}
+
+/// Parse mountinfo, e.g.
+/// `36 35 98:0 /mnt1 /mnt/parent rw,noatime master:1 - ext3 /dev/root rw,errors=continue`
+fn parse_mountinfo(contents: &str) -> Vec<MountEntry> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (mount, fs) = line.split_once(" - ")?;
+            let mut fields = mount.split(' ');
+            let (major, minor) = fields.nth(2)?.split_once(':')?;
+            let mount_point = fields.nth(1)?;
+            let fstype = fs.split(' ').next()?;
+
+            Some(MountEntry {
+                device: libc::makedev(major.parse().ok()?, minor.parse().ok()?),
+                mount_point: PathBuf::from(unescape(mount_point)),
+                fstype: FsType::from_mountinfo(fstype),
+            })
+        })
+        .collect()
+}
+
+/// Undo mountinfo's octal escapes for space, tab, newline and backslash
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('\\') {
+        out.push_str(&rest[..i]);
+        let escaped = rest
+            .get(i + 1..i + 4)
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(char::from(byte));
+                rest = &rest[i + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[i + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn read_mountinfo() -> Vec<MountEntry> {
+    std::fs::read_to_string(MOUNTINFO)
+        .map(|contents| parse_mountinfo(&contents))
+        .unwrap_or_default()
+}
+
+/// Filesystem type of a device, cached per device id
+///
+/// Unknown devices trigger a fresh read of mountinfo, in case something was
+/// mounted after the cache was filled.
+pub fn fstype_of(device: u64) -> Option<FsType> {
+    static CACHE: OnceLock<Mutex<HashMap<u64, Option<FsType>>>> = OnceLock::new();
+    let mut cache = CACHE.get_or_init(Default::default).lock().unwrap();
+
+    if let Some(fstype) = cache.get(&device) {
+        return *fstype;
+    }
+    for entry in read_mountinfo() {
+        cache.insert(entry.device, Some(entry.fstype));
+    }
+    *cache.entry(device).or_insert(None)
+}
+
+/// Check whether a directory is listed as a mount point in mountinfo
+pub fn is_mount_point(path: &Path) -> bool {
+    static MOUNT_POINTS: OnceLock<HashSet<PathBuf>> = OnceLock::new();
+    let mount_points = MOUNT_POINTS.get_or_init(|| {
+        read_mountinfo()
+            .into_iter()
+            .map(|entry| entry.mount_point)
+            .collect()
+    });
+
+    // mountinfo lists canonical paths
+    path.canonicalize()
+        .is_ok_and(|path| mount_points.contains(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mountinfo() {
+        let entries = parse_mountinfo(
+            "22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw\n\
+             40 22 0:35 / /mnt/my\\040share rw - nfs4 server:/export rw\n\
+             41 22 0:36 / /home/me/remote rw,nosuid - fuse.sshfs me@host: rw\n\
+             42 22 0:37 / /weird rw - someotherfs none rw\n",
+        );
+
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].device, libc::makedev(8, 1));
+        assert_eq!(entries[0].fstype, FsType::Ext4);
+        assert_eq!(entries[1].mount_point, PathBuf::from("/mnt/my share"));
+        assert_eq!(entries[1].fstype, FsType::Nfs);
+        assert_eq!(entries[2].fstype, FsType::Fuse);
+        assert_eq!(entries[3].fstype, FsType::Other);
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::mem::MaybeUninit;
+use std::sync::{Mutex, OnceLock};
+
+/// Matcher for user and group ids, resolved from names at typecheck time
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IdMatcher {
+    Equals(u32),
+    NotEquals(u32),
+    In(HashSet<u32>),
+}
+
+impl IdMatcher {
+    pub fn is_match(&self, id: u32) -> bool {
+        match self {
+            IdMatcher::Equals(x) => id == *x,
+            IdMatcher::NotEquals(x) => id != *x,
+            IdMatcher::In(set) => set.contains(&id),
+        }
+    }
+}
+
+/// Run a reentrant passwd/group lookup, growing the scratch buffer on `ERANGE`
+///
+/// `extract` runs while the buffer is still alive, since the entry's string
+/// fields point into it.
+fn lookup<T, R>(
+    mut call: impl FnMut(*mut T, *mut libc::c_char, usize, *mut *mut T) -> libc::c_int,
+    extract: impl FnOnce(&T) -> R,
+) -> Option<R> {
+    let mut buf: Vec<libc::c_char> = vec![0; 1024];
+    loop {
+        let mut entry = MaybeUninit::<T>::uninit();
+        let mut result = std::ptr::null_mut();
+        let rc = call(entry.as_mut_ptr(), buf.as_mut_ptr(), buf.len(), &mut result);
+        if rc == libc::ERANGE && buf.len() < 1 << 20 {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if rc != 0 || result.is_null() {
+            return None;
+        }
+        // SAFETY: a non-null result means the entry was filled in
+        return Some(extract(unsafe { entry.assume_init_ref() }));
+    }
+}
+
+/// Look up a user name in the passwd database
+pub fn user_id(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+    lookup(
+        // SAFETY: all pointers are valid for the duration of the call
+        |pwd, buf, len, result| unsafe { libc::getpwnam_r(name.as_ptr(), pwd, buf, len, result) },
+        |pwd: &libc::passwd| pwd.pw_uid,
+    )
+}
+
+/// Look up a group name in the group database
+pub fn group_id(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+    lookup(
+        // SAFETY: all pointers are valid for the duration of the call
+        |grp, buf, len, result| unsafe { libc::getgrnam_r(name.as_ptr(), grp, buf, len, result) },
+        |grp: &libc::group| grp.gr_gid,
+    )
+}
+
+/// Check for a passwd entry, cached per uid since most trees have few owners
+pub fn user_exists(uid: u32) -> bool {
+    static CACHE: OnceLock<Mutex<HashMap<u32, bool>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    *cache.lock().unwrap().entry(uid).or_insert_with(|| {
+        lookup(
+            // SAFETY: all pointers are valid for the duration of the call
+            |pwd, buf, len, result| unsafe { libc::getpwuid_r(uid, pwd, buf, len, result) },
+            |_: &libc::passwd| (),
+        )
+        .is_some()
+    })
+}
+
+/// Check for a group entry, cached per gid
+pub fn group_exists(gid: u32) -> bool {
+    static CACHE: OnceLock<Mutex<HashMap<u32, bool>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    *cache.lock().unwrap().entry(gid).or_insert_with(|| {
+        lookup(
+            // SAFETY: all pointers are valid for the duration of the call
+            |grp, buf, len, result| unsafe { libc::getgrgid_r(gid, grp, buf, len, result) },
+            |_: &libc::group| (),
+        )
+        .is_some()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_lookups() {
+        assert_eq!(user_id("root"), Some(0));
+        assert!(user_exists(0));
+        assert_eq!(user_id("no-such-user-detect"), None);
+        assert_eq!(user_id("nul\0byte"), None);
+    }
+}
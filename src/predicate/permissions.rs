@@ -0,0 +1,107 @@
+use std::fs::Metadata;
+use std::os::unix::fs::MetadataExt;
+use std::sync::OnceLock;
+
+/// Permission bits, including setuid/setgid/sticky (`mode & 0o7777`)
+pub fn permission_bits(metadata: &Metadata) -> u64 {
+    u64::from(metadata.mode() & 0o7777)
+}
+
+/// Render permission bits in `ls -l` symbolic form, e.g. `rwxr-x---` or `rwsr-xr-t`
+pub fn symbolic_mode(mode: u64) -> String {
+    // (read, write, execute, special bit, special char when executable)
+    let triads = [
+        (0o400, 0o200, 0o100, 0o4000, 's'),
+        (0o040, 0o020, 0o010, 0o2000, 's'),
+        (0o004, 0o002, 0o001, 0o1000, 't'),
+    ];
+
+    let mut out = String::with_capacity(9);
+    for (r, w, x, special, special_char) in triads {
+        out.push(if mode & r != 0 { 'r' } else { '-' });
+        out.push(if mode & w != 0 { 'w' } else { '-' });
+        out.push(match (mode & x != 0, mode & special != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    out
+}
+
+/// Kind of access checked for the current user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Effective uid, effective gid and supplementary groups of this process
+struct Credentials {
+    euid: u32,
+    groups: Vec<u32>,
+}
+
+fn credentials() -> &'static Credentials {
+    static CREDENTIALS: OnceLock<Credentials> = OnceLock::new();
+    CREDENTIALS.get_or_init(|| {
+        // SAFETY: these calls have no preconditions and only read process state
+        let (euid, egid) = unsafe { (libc::geteuid(), libc::getegid()) };
+
+        let mut groups = vec![egid];
+        let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+        if count > 0 {
+            let mut supplementary: Vec<libc::gid_t> = vec![0; count as usize];
+            let count = unsafe { libc::getgroups(count, supplementary.as_mut_ptr()) };
+            if count > 0 {
+                supplementary.truncate(count as usize);
+                groups.extend(supplementary);
+            }
+        }
+
+        Credentials { euid, groups }
+    })
+}
+
+/// Check whether the current user may read or write an entity, based on its permission bits
+///
+/// Follows the owner/group/other precedence used by `access(2)`, with root
+/// always granted access. ACLs and read-only mounts are not considered.
+pub fn has_access(metadata: &Metadata, access: Access) -> bool {
+    let creds = credentials();
+    if creds.euid == 0 {
+        return true;
+    }
+
+    let mode = metadata.mode();
+    let shift = if metadata.uid() == creds.euid {
+        6
+    } else if creds.groups.contains(&metadata.gid()) {
+        3
+    } else {
+        0
+    };
+
+    let bit = match access {
+        Access::Read => 0o4,
+        Access::Write => 0o2,
+    };
+    (mode >> shift) & bit != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbolic_mode() {
+        assert_eq!(symbolic_mode(0o644), "rw-r--r--");
+        assert_eq!(symbolic_mode(0o750), "rwxr-x---");
+        assert_eq!(symbolic_mode(0o4755), "rwsr-xr-x");
+        assert_eq!(symbolic_mode(0o2644), "rw-r-Sr--");
+        assert_eq!(symbolic_mode(0o1777), "rwxrwxrwt");
+        assert_eq!(symbolic_mode(0o1776), "rwxrwxrwT");
+        assert_eq!(symbolic_mode(0), "---------");
+    }
+}
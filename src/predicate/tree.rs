@@ -0,0 +1,124 @@
+//! Predicates that evaluate a sub-expression against other entries in the tree
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::Metadata;
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::SystemTime;
+
+use crate::expr::Expr;
+use crate::parser::typed::TemporalSelector;
+
+use super::{timestamp_of, Predicate};
+
+/// Which entries the sub-expression is evaluated against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeRelation {
+    /// `has_child(expr)` - any direct entry of this directory
+    Child,
+    /// `has_descendant(expr, depth<=N)` - any entry below this directory,
+    /// at most `N` levels down if limited
+    Descendant(Option<u32>),
+    /// `ancestor(expr)` - any entry of a directory above this one, up to
+    /// the search root
+    Ancestor,
+    /// `sibling(expr)` - any other entry of this entry's directory
+    Sibling,
+}
+
+/// `has_child(...)`, `has_descendant(...)`, `ancestor(...)`, `sibling(...)`
+///
+/// The sub-expression is a full typechecked expression, evaluated with
+/// `eval::fs::eval` on each candidate entry. Results are memoized per path
+/// and per directory for the whole walk, so neighbouring entries don't redo
+/// each other's work.
+#[derive(Debug)]
+pub struct TreePredicate {
+    pub relation: TreeRelation,
+    pub expr: Box<Expr<Predicate>>,
+    memo: Memo,
+}
+
+impl TreePredicate {
+    pub fn new(relation: TreeRelation, expr: Expr<Predicate>) -> Self {
+        Self {
+            relation,
+            expr: Box::new(expr),
+            memo: Memo::default(),
+        }
+    }
+
+    pub(crate) fn memo(&self) -> MutexGuard<'_, TreeMemo> {
+        // A panic mid-insert can't leave a map in a state worth discarding
+        self.memo.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// Two tree predicates are the same query regardless of what they've cached
+impl PartialEq for TreePredicate {
+    fn eq(&self, other: &Self) -> bool {
+        self.relation == other.relation && self.expr == other.expr
+    }
+}
+
+impl Eq for TreePredicate {}
+
+/// Totals over everything below a directory that the walk visits, for
+/// `tree_size`, `tree_files` and `tree_newest_mtime`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeTotals {
+    /// Apparent size of the regular files below
+    pub size: u64,
+    /// Number of regular files below
+    pub files: u64,
+    /// Newest mtime of the directory itself and every entry below
+    pub newest_mtime: SystemTime,
+}
+
+impl TreeTotals {
+    /// Totals of a directory before any of its entries are added
+    pub fn new(dir: &Metadata) -> Self {
+        Self {
+            size: 0,
+            files: 0,
+            newest_mtime: timestamp_of(TemporalSelector::Modified, dir),
+        }
+    }
+
+    /// Count an entry somewhere below the directory
+    pub fn add(&mut self, metadata: &Metadata) {
+        if metadata.is_file() {
+            self.size += metadata.size();
+            self.files += 1;
+        }
+        self.newest_mtime = self
+            .newest_mtime
+            .max(timestamp_of(TemporalSelector::Modified, metadata));
+    }
+}
+
+/// Results cached across the walk. Locks are never held across an await.
+#[derive(Default)]
+pub(crate) struct TreeMemo {
+    /// Sub-expression result per entry
+    pub matches: HashMap<PathBuf, bool>,
+    /// "Any entry below this directory, at most N levels down"
+    pub subtrees: HashMap<(PathBuf, Option<u32>), bool>,
+    /// "This directory or one above it has a matching entry"
+    pub ancestors: HashMap<PathBuf, bool>,
+    /// Up to two matching entries per directory, enough to tell whether an
+    /// entry has a matching sibling other than itself
+    pub children: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+#[derive(Default)]
+struct Memo(Mutex<TreeMemo>);
+
+// Keep debug logs of the parsed expression readable
+impl fmt::Debug for Memo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Memo")
+    }
+}
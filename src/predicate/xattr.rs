@@ -0,0 +1,145 @@
+//! Extended attributes and inode flags, read during the metadata phase
+
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use super::StringMatcher;
+
+/// Extended attribute holding the SELinux security context
+pub const SELINUX_XATTR: &str = "security.selinux";
+
+// From linux/fs.h, not exported by libc
+const FS_IMMUTABLE_FL: libc::c_int = 0x0000_0010;
+const FS_APPEND_FL: libc::c_int = 0x0000_0020;
+
+/// Inode flags as shown by `lsattr`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InodeFlag {
+    Immutable,  // chattr +i
+    AppendOnly, // chattr +a
+}
+
+impl InodeFlag {
+    fn mask(self) -> libc::c_int {
+        match self {
+            InodeFlag::Immutable => FS_IMMUTABLE_FL,
+            InodeFlag::AppendOnly => FS_APPEND_FL,
+        }
+    }
+}
+
+/// Predicates over extended attributes and inode flags
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XattrPredicate {
+    /// `xattr:security.capability` - attribute is set
+    Exists { name: String },
+    /// `xattr:user.origin == ci` - attribute value, as text
+    Value {
+        name: String,
+        matcher: StringMatcher,
+    },
+    /// `immutable`, `append_only`
+    Flag(InodeFlag),
+}
+
+impl XattrPredicate {
+    pub fn is_match(&self, path: &Path) -> bool {
+        match self {
+            XattrPredicate::Exists { name } => read_xattr(path, name).is_some(),
+            XattrPredicate::Value { name, matcher } => read_xattr(path, name)
+                .is_some_and(|value| attribute_text(&value).is_some_and(|s| matcher.is_match(s))),
+            XattrPredicate::Flag(flag) => {
+                inode_flags(path).is_some_and(|flags| flags & flag.mask() != 0)
+            }
+        }
+    }
+}
+
+/// Attribute values are usually NUL-terminated C strings (SELinux labels
+/// always are), so compare them without the terminator. Binary values aren't
+/// text and match nothing, like a missing attribute.
+fn attribute_text(value: &[u8]) -> Option<&str> {
+    let value = value.strip_suffix(b"\0").unwrap_or(value);
+    std::str::from_utf8(value).ok()
+}
+
+/// Read an extended attribute, following symlinks like the rest of the
+/// metadata phase. Missing attributes and unsupported filesystems give `None`.
+#[cfg(target_os = "linux")]
+fn read_xattr(path: &Path, name: &str) -> Option<Vec<u8>> {
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let name = CString::new(name).ok()?;
+
+    // The value may change between the size query and the read, so retry on ERANGE
+    loop {
+        // SAFETY: a null buffer with size 0 asks for the value's length
+        let len = unsafe { libc::getxattr(path.as_ptr(), name.as_ptr(), std::ptr::null_mut(), 0) };
+        if len < 0 {
+            return None;
+        }
+
+        let mut buf = vec![0u8; len as usize];
+        // SAFETY: buf is valid for buf.len() bytes
+        let read = unsafe {
+            libc::getxattr(
+                path.as_ptr(),
+                name.as_ptr(),
+                buf.as_mut_ptr().cast(),
+                buf.len(),
+            )
+        };
+        if read >= 0 {
+            buf.truncate(read as usize);
+            return Some(buf);
+        }
+        if std::io::Error::last_os_error().raw_os_error() != Some(libc::ERANGE) {
+            return None;
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_xattr(_path: &Path, _name: &str) -> Option<Vec<u8>> {
+    None
+}
+
+/// Read inode flags with `FS_IOC_GETFLAGS`; only regular files and
+/// directories are opened, so devices and FIFOs never block
+#[cfg(target_os = "linux")]
+fn inode_flags(path: &Path) -> Option<libc::c_int> {
+    use std::os::fd::AsRawFd;
+
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_file() && !metadata.is_dir() {
+        return None;
+    }
+
+    let file = std::fs::File::open(path).ok()?;
+    // The kernel reads and writes an int despite the ioctl's declared long
+    let mut flags: libc::c_int = 0;
+    // SAFETY: the fd is open for the duration of the call and flags is a valid int
+    let rc = unsafe { libc::ioctl(file.as_raw_fd(), libc::FS_IOC_GETFLAGS, &mut flags) };
+    (rc == 0).then_some(flags)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn inode_flags(_path: &Path) -> Option<libc::c_int> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attribute_text() {
+        assert_eq!(
+            attribute_text(b"system_u:object_r:httpd_sys_content_t:s0\0"),
+            Some("system_u:object_r:httpd_sys_content_t:s0")
+        );
+        assert_eq!(attribute_text(b"ci"), Some("ci"));
+        assert_eq!(attribute_text(b""), Some(""));
+        assert_eq!(attribute_text(b"\xff\xfe"), None);
+    }
+}
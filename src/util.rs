@@ -59,3 +59,29 @@ pub fn parse_size(s: &str) -> Result<u64, String> {
 
     Ok((number * multiplier) as u64)
 }
+
+/// Format a byte count with the largest unit that keeps it at or above 1,
+/// using the same units `parse_size` accepts
+///
+/// # Examples
+/// ```
+/// use detect::util::format_size;
+/// assert_eq!(format_size(512), "512b");
+/// assert_eq!(format_size(1536), "1.5kb");
+/// assert_eq!(format_size(10 * 1024 * 1024), "10.0mb");
+/// ```
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["kb", "mb", "gb", "tb"];
+
+    if bytes < 1024 {
+        return format!("{bytes}b");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1}{}", UNITS[unit])
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{f, find_with, tree};
+use detect::{BinaryPolicy, RuntimeConfig};
+use tempfile::TempDir;
+
+/// Text and binary files that all mention "hello"
+fn setup() -> TempDir {
+    // A multi-byte character straddling the end of the first block
+    let mut split = "a".repeat(8191);
+    split.push_str("é hello\n");
+
+    // Only the first block is sniffed
+    let mut late_nul = b"hello\n".to_vec();
+    late_nul.extend(vec![b'a'; 10_000]);
+    late_nul.push(0);
+
+    tree([
+        f("src/hello.txt", "hello world\n"),
+        f("empty.txt", ""),
+        f("image.png", b"\x89PNG\r\n\x1a\n\0\0\0\rhello"),
+        f("latin1.txt", b"caf\xe9 hello\n"),
+        f("split.txt", split),
+        f("late_nul.log", late_nul),
+    ])
+}
+
+async fn find(tmp_dir: &TempDir, expr: &str, binary: BinaryPolicy) -> Vec<String> {
+    let config = RuntimeConfig {
+        binary,
+        ..Default::default()
+    };
+    find_with(tmp_dir, expr, config).await
+}
+
+#[tokio::test]
+async fn test_text_and_binary_aliases() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, "binary", BinaryPolicy::Search).await,
+        ["image.png", "latin1.txt"]
+    );
+    // Directories are neither
+    assert_eq!(
+        find(&tmp_dir, "text", BinaryPolicy::Search).await,
+        ["empty.txt", "late_nul.log", "split.txt", "src/hello.txt"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "NOT binary AND ext == txt", BinaryPolicy::Search).await,
+        ["empty.txt", "split.txt", "src/hello.txt"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "binary OR dir", BinaryPolicy::Search).await,
+        ["image.png", "latin1.txt", "src"]
+    );
+}
+
+#[tokio::test]
+async fn test_binary_policy() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, "content contains hello", BinaryPolicy::Search).await,
+        [
+            "image.png",
+            "late_nul.log",
+            "latin1.txt",
+            "split.txt",
+            "src/hello.txt"
+        ]
+    );
+    assert_eq!(
+        find(&tmp_dir, "content contains hello", BinaryPolicy::Skip).await,
+        ["late_nul.log", "split.txt", "src/hello.txt"]
+    );
+    // Patterns never match skipped files, so their negation does
+    assert_eq!(
+        find(
+            &tmp_dir,
+            "file AND NOT content ~= hel+o",
+            BinaryPolicy::Skip
+        )
+        .await,
+        ["empty.txt", "image.png", "latin1.txt"]
+    );
+
+    // Everything is text
+    assert_eq!(
+        find(&tmp_dir, "binary", BinaryPolicy::Text).await,
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        find(&tmp_dir, "text", BinaryPolicy::Text).await,
+        [
+            "empty.txt",
+            "image.png",
+            "late_nul.log",
+            "latin1.txt",
+            "split.txt",
+            "src/hello.txt"
+        ]
+    );
+    assert_eq!(
+        find(&tmp_dir, "content contains hello", BinaryPolicy::Text).await,
+        [
+            "image.png",
+            "late_nul.log",
+            "latin1.txt",
+            "split.txt",
+            "src/hello.txt"
+        ]
+    );
+}
@@ -0,0 +1,86 @@
+//! Helpers shared by the integration tests
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+
+use detect::parser::error::DetectError;
+use detect::RuntimeConfig;
+use slog::{o, Discard, Logger};
+use tempfile::TempDir;
+
+/// A file to create in a test tree, at a path relative to its root
+pub struct TestFile {
+    path: String,
+    content: Vec<u8>,
+}
+
+pub fn f(path: &str, content: impl AsRef<[u8]>) -> TestFile {
+    TestFile {
+        path: path.to_owned(),
+        content: content.as_ref().to_vec(),
+    }
+}
+
+/// A temp dir holding `files`, with their parent directories created
+pub fn tree(files: impl IntoIterator<Item = TestFile>) -> TempDir {
+    let tmp_dir = tempfile::Builder::new().prefix("detect").tempdir().unwrap();
+    for file in files {
+        let path = tmp_dir.path().join(file.path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, file.content).unwrap();
+    }
+    tmp_dir
+}
+
+/// Run `expr` under `root`, returning the matches as sorted paths relative
+/// to `root`
+async fn search(
+    root: &Path,
+    respect_gitignore: bool,
+    expr: &str,
+    config: RuntimeConfig,
+) -> Result<Vec<String>, DetectError> {
+    let mut found = Vec::new();
+    detect::parse_and_run_fs(
+        Logger::root(Discard, o!()),
+        root,
+        respect_gitignore,
+        expr.to_owned(),
+        config,
+        |p| found.push(p.strip_prefix(root).unwrap().to_string_lossy().to_string()),
+    )
+    .await?;
+
+    found.sort();
+    Ok(found)
+}
+
+/// Search every entry under `root`, as with `-i`
+pub async fn run_with(
+    root: impl AsRef<Path>,
+    expr: &str,
+    config: RuntimeConfig,
+) -> Result<Vec<String>, DetectError> {
+    search(root.as_ref(), false, expr, config).await
+}
+
+pub async fn run(root: impl AsRef<Path>, expr: &str) -> Result<Vec<String>, DetectError> {
+    run_with(root, expr, RuntimeConfig::default()).await
+}
+
+pub async fn find_with(root: impl AsRef<Path>, expr: &str, config: RuntimeConfig) -> Vec<String> {
+    run_with(root, expr, config).await.unwrap()
+}
+
+pub async fn find(root: impl AsRef<Path>, expr: &str) -> Vec<String> {
+    find_with(root, expr, RuntimeConfig::default()).await
+}
+
+/// Search `root` honouring gitignore files, as the CLI does by default
+pub async fn find_respecting_gitignore(root: impl AsRef<Path>, expr: &str) -> Vec<String> {
+    search(root.as_ref(), true, expr, RuntimeConfig::default())
+        .await
+        .unwrap()
+}
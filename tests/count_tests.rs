@@ -0,0 +1,105 @@
+mod common;
+
+use common::{f, find, run, tree};
+use detect::parser::error::DetectError;
+use std::fs;
+use tempfile::TempDir;
+
+/// Sources with different numbers of TODOs and unwraps
+fn setup() -> TempDir {
+    tree([
+        f("clean.rs", "fn main() {}\n"),
+        f("some.rs", "// TODO one\n// TODO two\n"),
+        f("many.rs", "// TODO\n".repeat(8)),
+        f(
+            "unwraps.rs",
+            "fn main() {\n".to_string() + &"    x.unwrap().unwrap();\n".repeat(6) + "}\n",
+        ),
+    ])
+}
+
+#[tokio::test]
+async fn test_count_call() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, "count(content contains TODO) > 5").await,
+        ["many.rs"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "count(content contains TODO) == 2").await,
+        ["some.rs"]
+    );
+    assert_eq!(
+        find(&tmp_dir, r#"count(content contains "fn main") == 1"#).await,
+        ["clean.rs", "unwraps.rs"]
+    );
+    // Files without a match count zero
+    assert_eq!(
+        find(&tmp_dir, "file AND count(content contains TODO) < 1").await,
+        ["clean.rs", "unwraps.rs"]
+    );
+}
+
+#[tokio::test]
+async fn test_count_suffix() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, r#"content ~= "unwrap\(\)" count >= 10"#).await,
+        ["unwraps.rs"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "content contains TODO count != 0 AND ext == rs").await,
+        ["many.rs", "some.rs"]
+    );
+}
+
+#[tokio::test]
+async fn test_count_anchors_and_word_boundaries() {
+    let tmp_dir = setup();
+    let root = tmp_dir.path();
+    fs::write(root.join("hashes.md"), "##x\n").unwrap();
+    fs::write(root.join("foofoo.txt"), "foofoo foo\n").unwrap();
+
+    // A match ending mid-file doesn't restart the search at the start of it
+    assert_eq!(
+        find(&tmp_dir, r#"content ~= "^#" count == 1"#).await,
+        ["hashes.md"]
+    );
+    assert!(find(&tmp_dir, r#"content ~= "^#" count > 1"#)
+        .await
+        .is_empty());
+    assert_eq!(
+        find(&tmp_dir, r#"content ~= "(?-u:\b)foo" count == 2"#).await,
+        ["foofoo.txt"]
+    );
+    assert!(find(&tmp_dir, r#"content ~= "(?-u:\b)foo" count > 2"#)
+        .await
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_count_errors() {
+    let tmp_dir = setup();
+
+    for expr in [
+        "count(content contains TODO)",
+        "count(name == foo) > 1",
+        "count(content ~= \"x*\") > 1",
+        "count(content contains TODO) & 2",
+        "count(content contains TODO, content contains FIXME) > 1",
+    ] {
+        assert!(
+            matches!(
+                run(&tmp_dir, expr).await,
+                Err(DetectError::InvalidArguments { .. })
+            ),
+            "{expr}"
+        );
+    }
+    assert!(matches!(
+        run(&tmp_dir, "count(content contains TODO) > many").await,
+        Err(DetectError::InvalidValue { .. })
+    ));
+}
@@ -0,0 +1,37 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{f, find, tree};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+
+#[tokio::test]
+async fn test_sparse_and_disk_usage() {
+    let tmp_dir = tree([f("dense.bin", vec![1u8; 64 * 1024]), f("sparse.img", "")]);
+
+    // A 10mb hole with nothing allocated, next to a fully written file
+    let sparse = fs::File::options()
+        .write(true)
+        .open(tmp_dir.path().join("sparse.img"))
+        .unwrap();
+    sparse.set_len(10 * 1024 * 1024).unwrap();
+
+    // Not every filesystem supports holes
+    let allocated = fs::metadata(tmp_dir.path().join("sparse.img"))
+        .unwrap()
+        .blocks();
+    if allocated * 512 >= 5 * 1024 * 1024 {
+        return;
+    }
+
+    assert_eq!(find(&tmp_dir, "sparse").await, ["sparse.img"]);
+    assert_eq!(
+        find(&tmp_dir, "size > 1mb").await,
+        ["sparse.img"],
+        "apparent size counts the hole"
+    );
+    assert!(find(&tmp_dir, "disk_usage > 1mb").await.is_empty());
+    assert_eq!(find(&tmp_dir, "file && du >= 64kb").await, ["dense.bin"]);
+    assert_eq!(find(&tmp_dir, "file && blocks >= 128").await, ["dense.bin"]);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::{f, tree};
+use slog::{o, Discard, Logger};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Large files that only differ after the partially hashed prefix, and small
+/// files that fit within it
+fn setup() -> TempDir {
+    let image = vec![7u8; 10_000];
+    let mut near_copy = image.clone();
+    near_copy[9_000] = 8;
+
+    tree([
+        f("assets/logo.png", &image),
+        f("assets/old/logo.png", &image),
+        f("assets/logo-v2.png", near_copy),
+        f("assets/banner.png", vec![7u8; 20_000]),
+        f("a.txt", "same"),
+        f("b.txt", "same"),
+        f("c.txt", "diff"),
+    ])
+}
+
+/// Run `expr` and group the matches, relative to the root
+async fn duplicates(tmp_dir: &TempDir, expr: &str) -> Vec<Vec<String>> {
+    let logger = Logger::root(Discard, o!());
+    let mut matches: Vec<PathBuf> = Vec::new();
+    detect::parse_and_run_fs(
+        logger.clone(),
+        tmp_dir.path(),
+        false,
+        expr.to_owned(),
+        detect::RuntimeConfig::default(),
+        |p| matches.push(p.to_path_buf()),
+    )
+    .await
+    .unwrap();
+
+    let mut groups: Vec<Vec<String>> = detect::duplicates::find_duplicates(&logger, &matches)
+        .into_iter()
+        .map(|group| {
+            let mut group: Vec<String> = group
+                .iter()
+                .map(|p| {
+                    p.strip_prefix(tmp_dir.path())
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string()
+                })
+                .collect();
+            group.sort();
+            group
+        })
+        .collect();
+    groups.sort();
+    groups
+}
+
+#[tokio::test]
+async fn test_duplicates() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        duplicates(&tmp_dir, "ext == png").await,
+        [["assets/logo.png", "assets/old/logo.png"]]
+    );
+    assert_eq!(
+        duplicates(&tmp_dir, "ext in [png, txt]").await,
+        [
+            ["a.txt", "b.txt"],
+            ["assets/logo.png", "assets/old/logo.png"]
+        ]
+    );
+    // Only matching files are grouped
+    assert_eq!(
+        duplicates(&tmp_dir, "ext == png AND path ~= old").await,
+        Vec::<Vec<String>>::new()
+    );
+    // Directories are never duplicates
+    assert_eq!(
+        duplicates(&tmp_dir, "dir OR ext == txt").await,
+        [["a.txt", "b.txt"]]
+    );
+}
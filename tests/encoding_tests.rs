@@ -0,0 +1,123 @@
+mod common;
+
+use common::{f, find_with, tree};
+use detect::predicate::TextEncoding;
+use detect::RuntimeConfig;
+use tempfile::TempDir;
+
+fn utf16(s: &str, big_endian: bool, bom: bool) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for unit in std::iter::once(0xfeff)
+        .filter(|_| bom)
+        .chain(s.encode_utf16())
+    {
+        if big_endian {
+            bytes.extend(unit.to_be_bytes());
+        } else {
+            bytes.extend(unit.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+/// The same text in several encodings, as Windows tools write it
+fn setup() -> TempDir {
+    tree([
+        f("le.txt", utf16("// TODO: caf\u{e9}\r\n", false, true)),
+        f("be.txt", utf16("// TODO: caf\u{e9}\r\n", true, true)),
+        f("nobom.txt", utf16("// TODO\r\n", false, false)),
+        f("bom8.txt", "\u{feff}TODO: caf\u{e9}\n"),
+        f("plain.txt", "TODO: caf\u{e9}\n"),
+        f("latin1.txt", b"TODO: caf\xe9\n"),
+    ])
+}
+
+async fn find(tmp_dir: &TempDir, expr: &str, encoding: Option<TextEncoding>) -> Vec<String> {
+    let config = RuntimeConfig {
+        encoding,
+        ..Default::default()
+    };
+    find_with(tmp_dir, expr, config).await
+}
+
+#[tokio::test]
+async fn test_content_transcoded_from_bom() {
+    let tmp_dir = setup();
+
+    // UTF-16 files with a BOM are searched as text; without one they're raw bytes
+    assert_eq!(
+        find(&tmp_dir, r#"content contains "TODO: café""#, None).await,
+        ["be.txt", "bom8.txt", "le.txt", "plain.txt"]
+    );
+    // The BOM itself is stripped
+    assert_eq!(
+        find(&tmp_dir, r#"content ~= "^//""#, None).await,
+        ["be.txt", "le.txt"]
+    );
+    // Files with a UTF-16 BOM aren't binary
+    assert_eq!(
+        find(&tmp_dir, "binary", None).await,
+        ["latin1.txt", "nobom.txt"]
+    );
+}
+
+#[tokio::test]
+async fn test_encoding_override() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(
+            &tmp_dir,
+            "content contains TODO",
+            Some(TextEncoding::Utf16Le)
+        )
+        .await,
+        ["le.txt", "nobom.txt"]
+    );
+    assert_eq!(
+        find(
+            &tmp_dir,
+            r#"content contains "café""#,
+            Some(TextEncoding::Latin1)
+        )
+        .await,
+        ["latin1.txt"]
+    );
+}
+
+#[tokio::test]
+async fn test_encoding_selector_and_bom() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, "encoding == utf-16le", None).await,
+        ["le.txt"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "encoding in [utf8, latin1]", None).await,
+        ["bom8.txt", "latin1.txt", "plain.txt"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "bom", None).await,
+        ["be.txt", "bom8.txt", "le.txt"]
+    );
+    // Binary files (NUL bytes, no BOM) have no encoding
+    assert_eq!(
+        find(&tmp_dir, "encoding != utf8 AND NOT bom", None).await,
+        ["latin1.txt", "nobom.txt"]
+    );
+}
+
+#[tokio::test]
+async fn test_digests_see_raw_bytes() {
+    let tmp_dir = setup();
+    let expected = detect::predicate::hash_file(
+        detect::predicate::HashAlgorithm::Sha256,
+        &tmp_dir.path().join("le.txt"),
+    )
+    .unwrap();
+
+    // Decoding for a content pattern in the same query doesn't change the digest
+    let expr = format!("sha256 == {expected:?} AND content contains TODO");
+    assert_eq!(find(&tmp_dir, &expr, None).await, ["le.txt"]);
+}
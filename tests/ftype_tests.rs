@@ -0,0 +1,91 @@
+mod common;
+
+use common::{f, find_with, tree};
+use detect::predicate::FileTypeDefs;
+use detect::RuntimeConfig;
+use tempfile::TempDir;
+
+fn setup() -> TempDir {
+    tree([
+        f("src/main.rs", ""),
+        f("web/app.tsx", ""),
+        f("web/style.css", ""),
+        f("web/App.vue", ""),
+        f("Cargo.toml", ""),
+        f("settings.ini", ""),
+        f("api.proto", ""),
+    ])
+}
+
+async fn find(tmp_dir: &TempDir, expr: &str, file_types: FileTypeDefs) -> Vec<String> {
+    let config = RuntimeConfig {
+        file_types,
+        ..Default::default()
+    };
+    find_with(tmp_dir, expr, config).await
+}
+
+#[tokio::test]
+async fn test_builtin_file_types() {
+    let tmp_dir = setup();
+    let defaults = FileTypeDefs::default;
+
+    assert_eq!(
+        find(&tmp_dir, "ftype == rust", defaults()).await,
+        ["src/main.rs"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "ftype == web", defaults()).await,
+        ["web/app.tsx", "web/style.css"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "ftype in [web, config]", defaults()).await,
+        ["Cargo.toml", "settings.ini", "web/app.tsx", "web/style.css"]
+    );
+    // Types match file names, so directories only match `!=`
+    assert_eq!(
+        find(&tmp_dir, "ftype != config AND NOT path ~= web", defaults()).await,
+        ["api.proto", "src", "src/main.rs"]
+    );
+}
+
+#[tokio::test]
+async fn test_config_file_types() {
+    let tmp_dir = setup();
+
+    let mut file_types = FileTypeDefs::default();
+    file_types
+        .extend_from_toml(
+            r#"
+            [types]
+            web = ["*.vue", "*.tsx"]
+            schema = "*.proto"
+            "#,
+        )
+        .unwrap();
+
+    // Config types replace built-in types of the same name
+    assert_eq!(
+        find(&tmp_dir, "ftype == web", file_types.clone()).await,
+        ["web/App.vue", "web/app.tsx"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "ftype in [schema, rust]", file_types).await,
+        ["api.proto", "src/main.rs"]
+    );
+}
+
+#[test]
+fn test_invalid_config() {
+    let mut file_types = FileTypeDefs::default();
+    assert!(file_types
+        .extend_from_toml("[types]\nmy-type = \"*.x\"")
+        .is_err());
+    assert!(file_types.extend_from_toml("[types]\nbad = [1]").is_err());
+    assert!(file_types
+        .extend_from_toml("[types]\nbad = \"a{\"")
+        .is_err());
+    assert!(file_types.extend_from_toml("types = 1").is_err());
+    // Other tables are left alone
+    assert!(file_types.extend_from_toml("[other]\nkey = 1").is_ok());
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::{f, find, tree};
+use tempfile::TempDir;
+
+const HELLO_SHA256: &str = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+const EMPTY_BLAKE3: &str = "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262";
+
+/// A tree with a vendored copy of one file, a near-copy and an empty file
+fn setup() -> TempDir {
+    tree([
+        f("hello.txt", "hello\n"),
+        f("vendor/lib/hello.txt", "hello\n"),
+        f("vendor/lib/hello2.txt", "hello!\n"),
+        f("empty.txt", ""),
+    ])
+}
+
+#[tokio::test]
+async fn test_hash_selectors() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, &format!("sha256 == {HELLO_SHA256}")).await,
+        ["hello.txt", "vendor/lib/hello.txt"]
+    );
+    // Hex digests are case insensitive
+    assert_eq!(
+        find(
+            &tmp_dir,
+            &format!("sha256 == {}", HELLO_SHA256.to_uppercase())
+        )
+        .await,
+        ["hello.txt", "vendor/lib/hello.txt"]
+    );
+    assert_eq!(
+        find(
+            &tmp_dir,
+            &format!("sha256 in [{HELLO_SHA256}, {EMPTY_SHA256}]")
+        )
+        .await,
+        ["empty.txt", "hello.txt", "vendor/lib/hello.txt"]
+    );
+    // Directories have no contents to hash
+    assert_eq!(
+        find(&tmp_dir, &format!("blake3 in [{EMPTY_BLAKE3}]")).await,
+        ["empty.txt"]
+    );
+    assert_eq!(
+        find(&tmp_dir, &format!("NOT sha256 == {HELLO_SHA256} AND file")).await,
+        ["empty.txt", "vendor/lib/hello2.txt"]
+    );
+}
+
+#[tokio::test]
+async fn test_hash_with_content_predicates() {
+    let tmp_dir = setup();
+
+    // Both are evaluated from the same read of each file
+    assert_eq!(
+        find(
+            &tmp_dir,
+            &format!("content contains hello AND NOT sha256 == {HELLO_SHA256}")
+        )
+        .await,
+        ["vendor/lib/hello2.txt"]
+    );
+    assert_eq!(
+        find(
+            &tmp_dir,
+            &format!("content contains \"!\" OR blake3 == {EMPTY_BLAKE3}")
+        )
+        .await,
+        ["empty.txt", "vendor/lib/hello2.txt"]
+    );
+}
+
+#[tokio::test]
+async fn test_content_eq() {
+    let tmp_dir = setup();
+    let reference = tmp_dir.path().join("hello.txt");
+
+    assert_eq!(
+        find(
+            &tmp_dir,
+            &format!("content_eq(\"{}\")", reference.display())
+        )
+        .await,
+        ["hello.txt", "vendor/lib/hello.txt"]
+    );
+    assert_eq!(
+        find(
+            &tmp_dir,
+            &format!("content_eq(\"{}\") AND path ~= vendor", reference.display())
+        )
+        .await,
+        ["vendor/lib/hello.txt"]
+    );
+}
@@ -0,0 +1,102 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{f, find_with, tree};
+use detect::RuntimeConfig;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use tempfile::TempDir;
+
+/// A tree with one file reachable through three hard links and one plain file
+fn setup() -> TempDir {
+    let tmp_dir = tree([f("data.bin", "payload"), f("single.txt", "payload")]);
+    let root = tmp_dir.path();
+    fs::create_dir_all(root.join("backup/1")).unwrap();
+    fs::create_dir_all(root.join("backup/2")).unwrap();
+    fs::hard_link(root.join("data.bin"), root.join("backup/1/data.bin")).unwrap();
+    fs::hard_link(root.join("data.bin"), root.join("backup/2/data.bin")).unwrap();
+
+    tmp_dir
+}
+
+async fn find(tmp_dir: &TempDir, expr: &str, unique_inodes: bool) -> Vec<String> {
+    let config = RuntimeConfig {
+        unique_inodes,
+        ..Default::default()
+    };
+    find_with(tmp_dir, expr, config).await
+}
+
+#[tokio::test]
+async fn test_link_selectors() {
+    let tmp_dir = setup();
+    let metadata = fs::metadata(tmp_dir.path().join("data.bin")).unwrap();
+
+    assert_eq!(
+        find(&tmp_dir, "file && nlink == 3", false).await,
+        ["backup/1/data.bin", "backup/2/data.bin", "data.bin"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "hardlinked", false).await,
+        ["backup/1/data.bin", "backup/2/data.bin", "data.bin"]
+    );
+    assert_eq!(
+        find(&tmp_dir, &format!("inode == {}", metadata.ino()), false).await,
+        ["backup/1/data.bin", "backup/2/data.bin", "data.bin"]
+    );
+    assert_eq!(
+        find(
+            &tmp_dir,
+            &format!("file && device != {}", metadata.dev()),
+            false
+        )
+        .await,
+        Vec::<String>::new()
+    );
+}
+
+#[tokio::test]
+async fn test_unique_inodes() {
+    let tmp_dir = setup();
+
+    // Every link is reported by default
+    assert_eq!(
+        find(&tmp_dir, "content contains payload", false)
+            .await
+            .len(),
+        4
+    );
+
+    // Only one link per physical file with --unique-inodes
+    let found = find(&tmp_dir, "content contains payload", true).await;
+    assert_eq!(found.len(), 2);
+    assert!(found.contains(&"single.txt".to_string()));
+    assert_eq!(found.iter().filter(|p| p.ends_with("data.bin")).count(), 1);
+}
+
+#[tokio::test]
+async fn test_unique_inodes_non_matching_link_first() {
+    let tmp_dir = tree([f("a.txt", "payload")]);
+    let root = tmp_dir.path();
+    fs::hard_link(root.join("a.txt"), root.join("b.txt")).unwrap();
+
+    // Whichever link is walked first, a link that doesn't match doesn't hide
+    // one that does
+    for name in ["a.txt", "b.txt"] {
+        assert_eq!(
+            find(&tmp_dir, &format!("name == {name}"), true).await,
+            [name]
+        );
+        // Also when directories are decided after their contents
+        assert_eq!(
+            find(
+                &tmp_dir,
+                &format!("name == {name} OR (dir AND tree_files > 100)"),
+                true
+            )
+            .await,
+            [name]
+        );
+    }
+}
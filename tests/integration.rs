@@ -1063,3 +1063,48 @@ async fn test_binary_and_non_utf8_content() {
     found.sort();
     assert_eq!(found, vec!["binary.dat", "mixed.dat"]);
 }
+
+#[tokio::test]
+async fn test_empty_and_entries() {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("detect-empty")
+        .tempdir()
+        .unwrap();
+    let root = tmp_dir.path();
+
+    create_dir_all(root.join("empty_dir")).unwrap();
+    create_dir_all(root.join("full_dir/nested")).unwrap();
+    std::fs::write(root.join("full_dir/a.txt"), "a").unwrap();
+    std::fs::write(root.join("full_dir/b.txt"), "").unwrap();
+    std::fs::write(root.join("full_dir/.hidden"), "").unwrap();
+    std::fs::write(root.join("empty.txt"), "").unwrap();
+
+    let cases: Vec<(&str, &[&str])> = vec![
+        (
+            "empty",
+            &[".hidden", "b.txt", "empty.txt", "empty_dir", "nested"],
+        ),
+        ("dir AND empty", &["empty_dir", "nested"]),
+        ("dir AND NOT empty", &["full_dir"]),
+        // Every entry counts, hidden ones included; files have no entries
+        ("entries == 4", &["full_dir"]),
+        ("children > 1", &["full_dir"]),
+        ("entries == 0", &["empty_dir", "nested"]),
+    ];
+
+    for (expr, expected) in cases {
+        let mut found = Vec::new();
+        detect::parse_and_run_fs(
+            test_logger(),
+            root,
+            false,
+            expr.to_owned(),
+            detect::RuntimeConfig::default(),
+            |p| found.push(p.file_name().unwrap().to_string_lossy().to_string()),
+        )
+        .await
+        .unwrap();
+        found.sort();
+        assert_eq!(found, expected, "Failed for expression: {expr}");
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{f, find, tree};
+use tempfile::TempDir;
+
+/// Sources named by extension or convention, and extensionless scripts
+fn setup() -> TempDir {
+    tree([
+        f("src/main.rs", "fn main() {}\n"),
+        f("src/util.py", "def util(): pass\n"),
+        f("Dockerfile", "FROM alpine\n"),
+        f("Makefile", "all:\n\ttrue\n"),
+        f("bin/deploy", "#!/usr/bin/env bash\nset -e\n"),
+        f("bin/migrate", "#!/usr/bin/python3\nprint()\n"),
+        f("bin/setup", "# vim: set ft=ruby:\nputs 1\n"),
+        f("notes", "just some notes\n"),
+        // The extension wins over the first line
+        f("src/run.sh", "#!/usr/bin/env python3\n"),
+    ])
+}
+
+#[tokio::test]
+async fn test_lang_selector() {
+    let tmp_dir = setup();
+
+    assert_eq!(find(&tmp_dir, "lang == rust").await, ["src/main.rs"]);
+    assert_eq!(
+        find(&tmp_dir, "lang in [python, shell]").await,
+        ["bin/deploy", "bin/migrate", "src/run.sh", "src/util.py"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "lang in [dockerfile, make]").await,
+        ["Dockerfile", "Makefile"]
+    );
+    assert_eq!(find(&tmp_dir, "lang == ruby").await, ["bin/setup"]);
+    // Files in no known language only match `!=`, and directories never do
+    assert_eq!(
+        find(&tmp_dir, "lang != python AND NOT path ~= src").await,
+        ["Dockerfile", "Makefile", "bin/deploy", "bin/setup", "notes"]
+    );
+}
+
+#[tokio::test]
+async fn test_lang_with_content() {
+    let tmp_dir = setup();
+
+    // The header is read for scripts, and content patterns still see the
+    // whole file
+    assert_eq!(
+        find(&tmp_dir, "lang == shell AND content contains \"set -e\"").await,
+        ["bin/deploy"]
+    );
+}
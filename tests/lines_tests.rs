@@ -0,0 +1,169 @@
+mod common;
+
+use common::{f, find, find_with, tree};
+use detect::{BinaryPolicy, RuntimeConfig};
+use std::fs;
+use tempfile::TempDir;
+
+/// Files with each kind of line ending and whitespace problem
+fn setup() -> TempDir {
+    // A UTF-16 file is checked after decoding, so its NUL bytes don't count
+    let mut utf16 = vec![0xff, 0xfe];
+    utf16.extend("a\r\nb\r\n".encode_utf16().flat_map(u16::to_le_bytes));
+
+    tree([
+        f("unix.rs", "fn main() {\n    run();\n}\n"),
+        f("dos.bat", "@echo off\r\necho hi\r\n"),
+        f("mixed.txt", "one\r\ntwo\nthree\r\n"),
+        f("classic.txt", "one\rtwo\r"),
+        f("unterminated.txt", "no newline"),
+        f("trailing.md", "# Title \n\nbody\n"),
+        f("tabs.go", "func main() {\n\tfmt.Println()\n}\n"),
+        f("empty", ""),
+        f("utf16.txt", utf16),
+    ])
+}
+
+#[tokio::test]
+async fn test_eol_selector() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, "eol in [crlf, mixed]").await,
+        ["dos.bat", "mixed.txt", "utf16.txt"]
+    );
+    assert_eq!(find(&tmp_dir, "eol == cr").await, ["classic.txt"]);
+    assert_eq!(
+        find(&tmp_dir, "eol == none").await,
+        ["empty", "unterminated.txt"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "file AND eol != lf").await,
+        [
+            "classic.txt",
+            "dos.bat",
+            "empty",
+            "mixed.txt",
+            "unterminated.txt",
+            "utf16.txt"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_whitespace_aliases() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, "no_final_newline").await,
+        ["classic.txt", "unterminated.txt"]
+    );
+    assert_eq!(find(&tmp_dir, "trailing_whitespace").await, ["trailing.md"]);
+    assert_eq!(find(&tmp_dir, "tabs_indent").await, ["tabs.go"]);
+    // Checked in the same read as content patterns
+    assert_eq!(
+        find(&tmp_dir, "content contains main AND NOT tabs_indent").await,
+        ["unix.rs"]
+    );
+}
+
+#[tokio::test]
+async fn test_line_counts() {
+    let tmp_dir = setup();
+    let root = tmp_dir.path();
+    fs::write(root.join("bundle.min.js"), "x".repeat(5000) + "\n").unwrap();
+    fs::write(root.join("big.rs"), "let a = 1;\n".repeat(1500)).unwrap();
+
+    assert_eq!(find(&tmp_dir, "lines > 1000").await, ["big.rs"]);
+    assert_eq!(
+        find(&tmp_dir, "lines == 3").await,
+        ["mixed.txt", "tabs.go", "trailing.md", "unix.rs"]
+    );
+    // A last line without a line ending still counts, a lone CR doesn't end one
+    assert_eq!(
+        find(&tmp_dir, "lines == 1 AND no_final_newline").await,
+        ["classic.txt", "unterminated.txt"]
+    );
+    assert_eq!(find(&tmp_dir, "file AND lines == 0").await, ["empty"]);
+
+    assert_eq!(
+        find(&tmp_dir, "max_line_length > 200").await,
+        ["bundle.min.js"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "avg_line_length >= 10 AND lines > 1").await,
+        ["big.rs"]
+    );
+    // Lengths are in characters, after decoding
+    assert_eq!(find(&tmp_dir, "max_line_length == 1").await, ["utf16.txt"]);
+}
+
+#[tokio::test]
+async fn test_line_patterns() {
+    let tmp_dir = setup();
+
+    // `^` and `$` anchor at each line, without its line ending
+    assert_eq!(find(&tmp_dir, "line ~= \"^echo\"").await, ["dos.bat"]);
+    assert!(find(&tmp_dir, "content ~= \"^echo\"").await.is_empty());
+    assert_eq!(find(&tmp_dir, "line == \"echo hi\"").await, ["dos.bat"]);
+    assert_eq!(
+        find(&tmp_dir, "line == \"}\"").await,
+        ["tabs.go", "unix.rs"]
+    );
+    assert_eq!(find(&tmp_dir, "line contains Title").await, ["trailing.md"]);
+    // A lone CR doesn't end a line
+    assert_eq!(find(&tmp_dir, "line == two").await, ["mixed.txt"]);
+    // A last line without a line ending still counts
+    assert_eq!(
+        find(&tmp_dir, "line == \"no newline\"").await,
+        ["unterminated.txt"]
+    );
+    assert_eq!(find(&tmp_dir, "line == b").await, ["utf16.txt"]);
+
+    // A file without lines has none that fail
+    assert_eq!(
+        find(&tmp_dir, "all_lines ~= \"^[a-z]+$\"").await,
+        ["empty", "mixed.txt", "utf16.txt"]
+    );
+    // Empty lines need to match too
+    assert!(
+        find(&tmp_dir, "name == trailing.md AND all_lines ~= \"\\S\"")
+            .await
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_line_checks_skip_binary() {
+    let tmp_dir = setup();
+    // Binary data that happens to have every line problem
+    fs::write(
+        tmp_dir.path().join("img.png"),
+        b"\x89PNG\r\n\x1a\n\0\0 \n\tIHDR",
+    )
+    .unwrap();
+    let find_with_binary = |expr, binary| {
+        let config = RuntimeConfig {
+            binary,
+            ..Default::default()
+        };
+        find_with(&tmp_dir, expr, config)
+    };
+
+    for expr in [
+        "trailing_whitespace",
+        "tabs_indent",
+        "no_final_newline",
+        "eol == mixed",
+        "lines > 1",
+        "max_line_length > 0",
+        "avg_line_length > 0",
+    ] {
+        assert!(find_with_binary(expr, BinaryPolicy::Search)
+            .await
+            .contains(&"img.png".to_string()));
+        assert!(!find_with_binary(expr, BinaryPolicy::Skip)
+            .await
+            .contains(&"img.png".to_string()));
+    }
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{f, find, tree};
+use tempfile::TempDir;
+
+/// A little-endian ELF header with the given object type
+fn elf(e_type: u16) -> Vec<u8> {
+    let mut header = b"\x7fELF\x02\x01\x01".to_vec();
+    header.resize(16, 0);
+    header.extend(e_type.to_le_bytes());
+    header.resize(4096, 0);
+    header
+}
+
+/// Files whose extensions don't all match their contents
+fn setup() -> TempDir {
+    tree([
+        f("logo.png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+        f("notes.txt", b"PK\x03\x04\x14\0\0\0\x08\0hello"),
+        f("readme.txt", "hello world\n"),
+        f("bin/tool", elf(2)),
+        f("bin/libfoo.so", elf(3)),
+        f("empty", ""),
+    ])
+}
+
+#[tokio::test]
+async fn test_mime_selector() {
+    let tmp_dir = setup();
+
+    assert_eq!(find(&tmp_dir, r#"mime == "image/png""#).await, ["logo.png"]);
+    // Magic numbers win over the extension
+    assert_eq!(
+        find(&tmp_dir, "ext == txt AND mime == application/zip").await,
+        ["notes.txt"]
+    );
+    assert_eq!(
+        find(
+            &tmp_dir,
+            r#"mime ~= "^application/x-(executable|sharedlib)""#
+        )
+        .await,
+        ["bin/libfoo.so", "bin/tool"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "mime in [text/plain, application/x-empty]").await,
+        ["empty", "readme.txt"]
+    );
+    // Directories have no MIME type
+    assert_eq!(
+        find(&tmp_dir, r#"mime != "image/png" AND NOT file"#).await,
+        Vec::<String>::new()
+    );
+}
+
+#[tokio::test]
+async fn test_mime_with_content() {
+    let tmp_dir = setup();
+
+    // Content patterns still see the whole file after the header is read
+    assert_eq!(
+        find(&tmp_dir, "mime ~= ^text/ AND content contains world").await,
+        ["readme.txt"]
+    );
+    assert_eq!(
+        find(
+            &tmp_dir,
+            "mime == application/zip OR content contains world"
+        )
+        .await,
+        ["notes.txt", "readme.txt"]
+    );
+}
@@ -0,0 +1,86 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use common::{f, find_with, tree};
+use detect::predicate::{fstype_of, EnumPredicate};
+use detect::RuntimeConfig;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+async fn find(root: &Path, expr: &str, one_file_system: bool) -> Vec<String> {
+    let config = RuntimeConfig {
+        one_file_system,
+        ..Default::default()
+    };
+    find_with(root, expr, config).await
+}
+
+#[tokio::test]
+async fn test_fstype() {
+    let tmp_dir = tree([f("a.txt", "a")]);
+
+    let device = fs::metadata(tmp_dir.path()).unwrap().dev();
+    let Some(fstype) = fstype_of(device) else {
+        return; // no mountinfo available
+    };
+
+    assert_eq!(
+        find(
+            tmp_dir.path(),
+            &format!("fstype == {}", fstype.as_str()),
+            false
+        )
+        .await,
+        ["a.txt"]
+    );
+    assert!(find(
+        tmp_dir.path(),
+        &format!("fstype != {}", fstype.as_str()),
+        false
+    )
+    .await
+    .is_empty());
+}
+
+#[tokio::test]
+async fn test_mountpoint() {
+    let tmp_dir = tree([f("sub/a.txt", "a")]);
+
+    assert!(find(tmp_dir.path(), "mountpoint", false).await.is_empty());
+
+    // /proc is a mount point on every Linux system with a mountinfo
+    if Path::new("/proc/self/mountinfo").exists() {
+        assert!(detect::predicate::is_mount_point(Path::new("/proc")));
+    }
+}
+
+#[tokio::test]
+async fn test_one_file_system() {
+    let tmp_dir = tree([f("sub/a.txt", "a")]);
+
+    // Staying on one filesystem still descends into ordinary directories
+    assert_eq!(
+        find(tmp_dir.path(), "file", true).await,
+        find(tmp_dir.path(), "file", false).await
+    );
+}
+
+#[tokio::test]
+async fn test_one_file_system_tree_predicates() {
+    // devpts is mounted below /dev on every Linux system with a mountinfo
+    let dev = Path::new("/dev");
+    if !detect::predicate::is_mount_point(&dev.join("pts")) || !dev.join("pts/ptmx").exists() {
+        return;
+    }
+
+    // The main walk lists /dev/pts but doesn't descend into it under -x, and
+    // neither do tree predicates
+    let expr = "name == pts AND has_child(name == ptmx)";
+    assert_eq!(find(dev, expr, false).await, ["pts"]);
+    assert!(find(dev, expr, true).await.is_empty());
+    assert!(!find(dev, "dir AND has_descendant(name == ptmx)", true)
+        .await
+        .contains(&"pts".to_string()));
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::{f, find, run, tree};
+use detect::parser::error::DetectError;
+use tempfile::TempDir;
+
+/// Sources with `unsafe` blocks and locks held at different distances from
+/// the lines that justify or misuse them
+fn setup() -> TempDir {
+    tree([
+        f(
+            "justified.rs",
+            "fn f() {\n    // SAFETY: the pointer is valid\n    unsafe { *p }\n}\n",
+        ),
+        f(
+            "far.rs",
+            "// SAFETY: somewhere else\n".to_string() + &"\n".repeat(10) + "unsafe { *p }\n",
+        ),
+        f("bare.rs", "unsafe { *p }\n"),
+        f(
+            "held.rs",
+            "let guard = state.lock().unwrap();\nlet x = 1;\nfetch().await;\n",
+        ),
+        f(
+            "dropped.rs",
+            "{\n    let guard = state.lock().unwrap();\n}\n".to_string()
+                + &"\n".repeat(10)
+                + "fetch().await;\n",
+        ),
+    ])
+}
+
+#[tokio::test]
+async fn test_near_call() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, r#"near("unsafe", "// SAFETY:", 3 lines)"#).await,
+        ["justified.rs"]
+    );
+    assert_eq!(
+        find(&tmp_dir, r#"near("unsafe", "// SAFETY:", 20 lines)"#).await,
+        ["far.rs", "justified.rs"]
+    );
+    assert_eq!(
+        find(
+            &tmp_dir,
+            r#"ext == rs AND content contains unsafe AND NOT near("unsafe", "SAFETY:", 3 lines)"#
+        )
+        .await,
+        ["bare.rs", "far.rs"]
+    );
+    // Content patterns as arguments, anchored per line
+    assert_eq!(
+        find(
+            &tmp_dir,
+            r#"near(content ~= "^\s*unsafe", line contains SAFETY, 1 line)"#
+        )
+        .await,
+        ["justified.rs"]
+    );
+}
+
+#[tokio::test]
+async fn test_within() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(
+            &tmp_dir,
+            r#"content contains "lock()" within 5 lines of "await""#
+        )
+        .await,
+        ["held.rs"]
+    );
+    assert_eq!(
+        find(
+            &tmp_dir,
+            r#"content ~= "lock\(\)" within 20 lines of "\.await""#
+        )
+        .await,
+        ["dropped.rs", "held.rs"]
+    );
+    assert!(
+        find(&tmp_dir, "content contains lock within 1 line of await")
+            .await
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_near_errors() {
+    let tmp_dir = setup();
+
+    for expr in [
+        r#"near("a", "b")"#,
+        r#"near("a", "b", many lines)"#,
+        r#"near("a", name == b, 3 lines)"#,
+        r#"near("a", "b", 3 lines) > 1"#,
+    ] {
+        assert!(
+            matches!(
+                run(&tmp_dir, expr).await,
+                Err(DetectError::InvalidArguments { .. })
+            ),
+            "{expr}"
+        );
+    }
+}
@@ -263,6 +263,137 @@ fn test_whitespace_handling() {
     assert_eq!(result.to_test_expr(), expected);
 }
 
+#[test]
+fn test_function_calls() {
+    use detect::parser::test_utils::{RawTestArg, RawTestCall, RawTestValue};
+
+    let result = RawParser::parse_raw_expr("has_child(name == Cargo.toml)").unwrap();
+    let expected = RawTestExpr::call(
+        "has_child",
+        vec![RawTestArg::Expr(RawTestExpr::string_predicate(
+            "name",
+            "==",
+            "Cargo.toml",
+        ))],
+    );
+    assert_eq!(result.to_test_expr(), expected);
+
+    // Bare values inside a call stop at the argument separator
+    let result = RawParser::parse_raw_expr("has_descendant(name == foo, depth<=3)").unwrap();
+    let expected = RawTestExpr::call(
+        "has_descendant",
+        vec![
+            RawTestArg::Expr(RawTestExpr::string_predicate("name", "==", "foo")),
+            RawTestArg::Expr(RawTestExpr::string_predicate("depth", "<=", "3")),
+        ],
+    );
+    assert_eq!(result.to_test_expr(), expected);
+
+    // ...but commas inside brackets are part of the value
+    let result = RawParser::parse_raw_expr("has_child(ext in [rs, toml])").unwrap();
+    let expected = RawTestExpr::call(
+        "has_child",
+        vec![RawTestArg::Expr(RawTestExpr::string_predicate(
+            "ext",
+            "in",
+            "[rs, toml]",
+        ))],
+    );
+    assert_eq!(result.to_test_expr(), expected);
+
+    // Quoted and bare arguments, plus a trailing comparison
+    let result = RawParser::parse_raw_expr(r#"f("a, b", 3 lines) > 5"#).unwrap();
+    let expected = RawTestExpr::Call(RawTestCall {
+        name: "f",
+        args: vec![
+            RawTestArg::Value(RawTestValue::Quoted("a, b")),
+            RawTestArg::Value(RawTestValue::Raw("3 lines")),
+        ],
+        comparison: Some((">", RawTestValue::Raw("5"))),
+    });
+    assert_eq!(result.to_test_expr(), expected);
+
+    // Calls combine with the usual boolean operators
+    let result = RawParser::parse_raw_expr("dir AND NOT has_child(tests)").unwrap();
+    let expected = RawTestExpr::and(
+        RawTestExpr::single_word("dir"),
+        RawTestExpr::not(RawTestExpr::call(
+            "has_child",
+            vec![RawTestArg::Expr(RawTestExpr::single_word("tests"))],
+        )),
+    );
+    assert_eq!(result.to_test_expr(), expected);
+
+    // Top-level bare values still accept commas
+    let result = RawParser::parse_raw_expr("name == a,b").unwrap();
+    let expected = RawTestExpr::string_predicate("name", "==", "a,b");
+    assert_eq!(result.to_test_expr(), expected);
+}
+
+#[test]
+fn test_counted_predicate() {
+    use detect::parser::test_utils::{RawTestArg, RawTestCall, RawTestValue};
+
+    // A trailing count is the same as wrapping the predicate in count(...)
+    let expected = RawTestExpr::Call(RawTestCall {
+        name: "count",
+        args: vec![RawTestArg::Expr(RawTestExpr::string_predicate(
+            "content", "contains", "TODO",
+        ))],
+        comparison: Some((">", RawTestValue::Raw("5"))),
+    });
+    let result = RawParser::parse_raw_expr("count(content contains TODO) > 5").unwrap();
+    assert_eq!(result.to_test_expr(), expected);
+    let result = RawParser::parse_raw_expr("content contains TODO count > 5").unwrap();
+    assert_eq!(result.to_test_expr(), expected);
+
+    // Only as a separate word
+    let result = RawParser::parse_raw_expr("content contains TODO AND counter").unwrap();
+    assert_eq!(
+        result.to_test_expr(),
+        RawTestExpr::and(
+            RawTestExpr::string_predicate("content", "contains", "TODO"),
+            RawTestExpr::single_word("counter"),
+        )
+    );
+}
+
+#[test]
+fn test_proximity_predicate() {
+    use detect::parser::test_utils::{RawTestArg, RawTestCall, RawTestValue};
+
+    // `within N lines of` is near(...) with the first predicate's selector and
+    // operator applied to the second value
+    let expected = RawTestExpr::Call(RawTestCall {
+        name: "near",
+        args: vec![
+            RawTestArg::Expr(RawTestExpr::string_predicate("content", "contains", "lock")),
+            RawTestArg::Expr(RawTestExpr::string_predicate(
+                "content", "contains", "await",
+            )),
+            RawTestArg::Value(RawTestValue::Raw("5 lines")),
+        ],
+        comparison: None,
+    });
+    let result =
+        RawParser::parse_raw_expr("near(content contains lock, content contains await, 5 lines)")
+            .unwrap();
+    assert_eq!(result.to_test_expr(), expected);
+    let result =
+        RawParser::parse_raw_expr("content contains lock within 5 lines of await").unwrap();
+    assert_eq!(result.to_test_expr(), expected);
+
+    // Only as separate words
+    let result = RawParser::parse_raw_expr("content contains lock AND within").unwrap();
+    assert_eq!(
+        result.to_test_expr(),
+        RawTestExpr::and(
+            RawTestExpr::string_predicate("content", "contains", "lock"),
+            RawTestExpr::single_word("within"),
+        )
+    );
+}
+
 #[test]
 fn test_edge_cases() {
     // Empty string value
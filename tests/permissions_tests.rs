@@ -0,0 +1,156 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{f, find, tree};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use tempfile::TempDir;
+
+/// Create files with the given permission bits in a fresh temp dir
+fn setup(files: &[(&str, u32)]) -> TempDir {
+    let tmp_dir = tree(files.iter().map(|(name, _)| f(name, "content")));
+    for (name, mode) in files {
+        let path = tmp_dir.path().join(name);
+        fs::set_permissions(path, fs::Permissions::from_mode(*mode)).unwrap();
+    }
+
+    tmp_dir
+}
+
+#[tokio::test]
+async fn test_mode_comparisons() {
+    let tmp_dir = setup(&[
+        ("private", 0o600),
+        ("shared", 0o644),
+        ("script.sh", 0o755),
+        ("world_writable", 0o666),
+    ]);
+
+    assert_eq!(
+        find(&tmp_dir, "type == file && mode == 0644").await,
+        ["shared"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "type == file && mode == 0o755").await,
+        ["script.sh"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "type == file && mode & 0o002").await,
+        ["world_writable"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "type == file && !(mode & 077)").await,
+        ["private"]
+    );
+}
+
+#[tokio::test]
+async fn test_symbolic_permissions() {
+    let tmp_dir = setup(&[("private", 0o640), ("script.sh", 0o750), ("shared", 0o644)]);
+
+    assert_eq!(
+        find(&tmp_dir, r#"perm == "rwxr-x---""#).await,
+        ["script.sh"]
+    );
+    assert_eq!(
+        find(&tmp_dir, r#"perm ~= "^rw-r-""#).await,
+        ["private", "shared"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "type == file && perm in [rw-r-----, rw-r--r--]").await,
+        ["private", "shared"]
+    );
+}
+
+#[tokio::test]
+async fn test_permission_aliases() {
+    let tmp_dir = setup(&[
+        ("plain", 0o644),
+        ("script.sh", 0o744),
+        ("setuid_bin", 0o4755),
+        ("setgid_bin", 0o2755),
+        ("sticky_file", 0o1644),
+    ]);
+
+    assert_eq!(
+        find(&tmp_dir, "file && executable").await,
+        ["script.sh", "setgid_bin", "setuid_bin"]
+    );
+    assert_eq!(find(&tmp_dir, "setuid").await, ["setuid_bin"]);
+    assert_eq!(find(&tmp_dir, "setgid").await, ["setgid_bin"]);
+    assert_eq!(find(&tmp_dir, "file && sticky").await, ["sticky_file"]);
+}
+
+#[tokio::test]
+async fn test_readable_writable() {
+    let tmp_dir = setup(&[("read_only", 0o444), ("read_write", 0o644)]);
+
+    assert_eq!(
+        find(&tmp_dir, "file && readable").await,
+        ["read_only", "read_write"]
+    );
+
+    // Root bypasses permission bits, so only check denial as a regular user
+    let is_root = unsafe { libc::geteuid() } == 0;
+    let writable = find(&tmp_dir, "file && writable").await;
+    if is_root {
+        assert_eq!(writable, ["read_only", "read_write"]);
+    } else {
+        assert_eq!(writable, ["read_write"]);
+    }
+}
+
+#[tokio::test]
+async fn test_owner_and_group() {
+    let tmp_dir = setup(&[("a.txt", 0o644), ("b.txt", 0o644)]);
+    let (uid, gid) = unsafe { (libc::geteuid(), libc::getegid()) };
+
+    assert_eq!(
+        find(&tmp_dir, &format!("file && uid == {uid}")).await,
+        ["a.txt", "b.txt"]
+    );
+    assert!(find(&tmp_dir, &format!("uid != {uid}")).await.is_empty());
+    assert_eq!(
+        find(&tmp_dir, &format!("file && gid == {gid}")).await,
+        ["a.txt", "b.txt"]
+    );
+
+    // Numeric ids are accepted wherever names are
+    assert_eq!(
+        find(&tmp_dir, &format!("file && user == {uid}")).await,
+        ["a.txt", "b.txt"]
+    );
+    assert_eq!(
+        find(&tmp_dir, &format!("file && group in [{gid}, root]")).await,
+        ["a.txt", "b.txt"]
+    );
+
+    let owned_by_root = find(&tmp_dir, "file && owner == root").await;
+    if uid == 0 {
+        assert_eq!(owned_by_root, ["a.txt", "b.txt"]);
+    } else {
+        assert!(owned_by_root.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_orphaned() {
+    let tmp_dir = setup(&[("owned", 0o644), ("orphan", 0o644)]);
+    assert!(find(&tmp_dir, "orphaned").await.is_empty());
+
+    // Handing a file to an unknown uid needs root
+    if unsafe { libc::geteuid() } != 0 {
+        return;
+    }
+    let unknown = (60000..65000)
+        .find(|&id| !detect::predicate::user_exists(id))
+        .unwrap();
+    std::os::unix::fs::chown(tmp_dir.path().join("orphan"), Some(unknown), None).unwrap();
+
+    assert_eq!(find(&tmp_dir, "orphaned").await, ["orphan"]);
+    assert_eq!(
+        find(&tmp_dir, &format!("uid == {unknown}")).await,
+        ["orphan"]
+    );
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::{f, find, tree};
+use tempfile::TempDir;
+
+/// Scripts run through various interpreters, and files without a shebang
+fn setup() -> TempDir {
+    tree([
+        f("bin/deploy", "#!/usr/bin/env bash\nset -e\n"),
+        f("bin/legacy", "#!/bin/sh\r\necho hi\r\n"),
+        f("bin/migrate.py", "#! /usr/bin/python3 -u\n"),
+        f("bin/serve", "#!/usr/bin/env -S node --inspect\n"),
+        f("lib.py", "import os\n#!/bin/bash\n"),
+    ])
+}
+
+#[tokio::test]
+async fn test_shebang_selector() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, "shebang ~= python3").await,
+        ["bin/migrate.py"]
+    );
+    assert_eq!(
+        find(&tmp_dir, r#"shebang == "/bin/sh""#).await,
+        ["bin/legacy"]
+    );
+    // Only the first line counts
+    assert_eq!(
+        find(&tmp_dir, "shebang contains bash").await,
+        ["bin/deploy"]
+    );
+}
+
+#[tokio::test]
+async fn test_interpreter_selector() {
+    let tmp_dir = setup();
+
+    assert_eq!(find(&tmp_dir, "interpreter == bash").await, ["bin/deploy"]);
+    assert_eq!(find(&tmp_dir, "interpreter == node").await, ["bin/serve"]);
+    assert_eq!(
+        find(&tmp_dir, "interpreter in [sh, python3]").await,
+        ["bin/legacy", "bin/migrate.py"]
+    );
+    // Scripts outside an approved list; files without a shebang only match `!=`
+    assert_eq!(
+        find(&tmp_dir, "interpreter != bash").await,
+        ["bin/legacy", "bin/migrate.py", "bin/serve", "lib.py"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "shebang ~= . AND NOT interpreter in [bash, sh]").await,
+        ["bin/migrate.py", "bin/serve"]
+    );
+}
@@ -504,3 +504,153 @@ async fn test_greater_less_or_equal_operators() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn test_reference_file_comparison() {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("detect-temporal-reference")
+        .tempdir()
+        .unwrap();
+
+    let now = SystemTime::now();
+    let files = vec![
+        ("stamp", 60 * 60),
+        ("fresh.rs", 0),
+        ("recent.rs", 30 * 60),
+        ("stale.rs", 2 * 60 * 60),
+    ];
+
+    for (name, age_secs) in &files {
+        let path = tmp_dir.path().join(name);
+        std::fs::write(&path, "content").unwrap();
+        let mtime = now - std::time::Duration::from_secs(*age_secs);
+        fs::File::open(&path).unwrap().set_modified(mtime).unwrap();
+    }
+
+    let stamp = tmp_dir.path().join("stamp");
+    let stamp = stamp.to_str().unwrap();
+
+    run_temporal_test(
+        &tmp_dir,
+        &format!("modified > mtime_of(\"{stamp}\")"),
+        vec!["fresh.rs", "recent.rs"],
+        vec!["stale.rs", "stamp"],
+    )
+    .await;
+
+    // Reference file is stat'ed once, so it compares equal to itself
+    run_temporal_test(
+        &tmp_dir,
+        &format!("modified <= mtime_of({stamp})"),
+        vec!["stale.rs", "stamp"],
+        vec!["fresh.rs", "recent.rs"],
+    )
+    .await;
+
+    run_temporal_test(
+        &tmp_dir,
+        &format!("ext == rs AND mtime < modified_of('{stamp}')"),
+        vec!["stale.rs"],
+        vec!["fresh.rs", "recent.rs", "stamp"],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_reference_file_sub_second() {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("detect-temporal-reference-nanos")
+        .tempdir()
+        .unwrap();
+
+    // All in the same second, like find's -newer
+    let second = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+    let files = vec![("before", 100), ("stamp", 500), ("after", 900)];
+    for (name, millis) in &files {
+        let path = tmp_dir.path().join(name);
+        std::fs::write(&path, "content").unwrap();
+        let mtime = second + std::time::Duration::from_millis(*millis);
+        fs::File::open(&path).unwrap().set_modified(mtime).unwrap();
+    }
+
+    let stamp = tmp_dir.path().join("stamp");
+    let stamp = stamp.to_str().unwrap();
+
+    run_temporal_test(
+        &tmp_dir,
+        &format!("modified > mtime_of({stamp})"),
+        vec!["after"],
+        vec!["before", "stamp"],
+    )
+    .await;
+
+    run_temporal_test(
+        &tmp_dir,
+        &format!("modified < mtime_of({stamp})"),
+        vec!["before"],
+        vec!["after", "stamp"],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_reference_file_missing_is_error() {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("detect-temporal-reference-missing")
+        .tempdir()
+        .unwrap();
+
+    let result = detect::parse_and_run_fs(
+        Logger::root(Discard, o!()),
+        tmp_dir.path(),
+        false,
+        "modified > mtime_of(\"does/not/exist\")".to_owned(),
+        detect::RuntimeConfig::default(),
+        |_| {},
+    )
+    .await;
+
+    assert!(matches!(
+        result,
+        Err(detect::parser::error::DetectError::InvalidValue { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_selector_valued_comparison() {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("detect-temporal-selector")
+        .tempdir()
+        .unwrap();
+
+    // Setting mtime bumps ctime to now, so backdated files have modified < created
+    let now = SystemTime::now();
+    let backdated = tmp_dir.path().join("backdated.txt");
+    let future = tmp_dir.path().join("future.txt");
+    std::fs::write(&backdated, "backdated").unwrap();
+    std::fs::write(&future, "future").unwrap();
+    fs::File::open(&backdated)
+        .unwrap()
+        .set_modified(now - std::time::Duration::from_secs(24 * 60 * 60))
+        .unwrap();
+    fs::File::open(&future)
+        .unwrap()
+        .set_modified(now + std::time::Duration::from_secs(24 * 60 * 60))
+        .unwrap();
+
+    run_temporal_test(
+        &tmp_dir,
+        "modified < created",
+        vec!["backdated.txt"],
+        vec!["future.txt"],
+    )
+    .await;
+
+    run_temporal_test(
+        &tmp_dir,
+        "mtime > ctime",
+        vec!["future.txt"],
+        vec!["backdated.txt"],
+    )
+    .await;
+}
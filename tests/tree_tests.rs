@@ -0,0 +1,284 @@
+mod common;
+
+use common::{f, find, find_respecting_gitignore, tree};
+use std::fs::create_dir_all;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use slog::{o, Discard, Logger};
+use tempfile::TempDir;
+
+fn test_logger() -> Logger {
+    Logger::root(Discard, o!())
+}
+
+/// A small workspace: two crates, one with tests, plus a docs tree
+fn setup() -> TempDir {
+    tree([
+        f("core/Cargo.toml", "[package]\nname = \"core\"\n"),
+        f("core/src/lib.rs", "pub fn f() {}\n"),
+        f("core/tests/it.rs", "#[test]\nfn t() {}\n"),
+        f("cli/Cargo.toml", "[package]\nname = \"cli\"\n"),
+        f("cli/src/main.rs", "fn main() {}\n"),
+        f("cli/src/commands/run.rs", "// TODO\n"),
+        f("docs/guide/intro.md", "# Intro\n"),
+    ])
+}
+
+#[tokio::test]
+async fn test_has_child() {
+    let tmp_dir = setup();
+    let root = tmp_dir.path();
+
+    assert_eq!(
+        find(
+            root,
+            "dir AND has_child(name == Cargo.toml) AND NOT has_child(name == tests)"
+        )
+        .await,
+        ["cli"]
+    );
+    assert_eq!(
+        find(root, "has_child(name == Cargo.toml)").await,
+        ["cli", "core"]
+    );
+
+    // Any expression works inside, including content predicates
+    assert_eq!(
+        find(root, "has_child(ext == rs AND content contains TODO)").await,
+        ["cli/src/commands"]
+    );
+
+    // Files never have children
+    assert_eq!(
+        find(root, "file AND has_child(name ~= .*)").await,
+        Vec::<String>::new()
+    );
+}
+
+#[tokio::test]
+async fn test_has_descendant() {
+    let tmp_dir = setup();
+    let root = tmp_dir.path();
+
+    assert_eq!(
+        find(root, "has_descendant(content contains TODO)").await,
+        ["cli", "cli/src", "cli/src/commands"]
+    );
+
+    // depth<=1 is the same as has_child
+    assert_eq!(
+        find(root, "has_descendant(name == run.rs, depth<=1)").await,
+        ["cli/src/commands"]
+    );
+    assert_eq!(
+        find(root, "has_descendant(name == run.rs, depth<=2)").await,
+        ["cli/src", "cli/src/commands"]
+    );
+    assert_eq!(
+        find(root, "has_descendant(name == run.rs, depth < 3)").await,
+        ["cli/src", "cli/src/commands"]
+    );
+
+    // Directories with no markdown anywhere below them
+    assert_eq!(
+        find(root, "dir AND depth == 1 AND NOT has_descendant(ext == md)").await,
+        ["cli", "core"]
+    );
+}
+
+#[tokio::test]
+async fn test_tree_predicates_see_hidden_entries() {
+    let tmp_dir = tree([f("project/.github/ci.yml", "on: push\n")]);
+
+    assert_eq!(
+        find(&tmp_dir, "has_child(name == .github)").await,
+        ["project"]
+    );
+}
+
+#[tokio::test]
+async fn test_tree_predicates_skip_vcs_and_ignored_entries() {
+    let tmp_dir = tree([
+        f("proj/.git/objects/secret.key", ""),
+        f("proj/.gitignore", "target/\n"),
+        f("proj/target/debug/build.key", ""),
+    ]);
+    let root = tmp_dir.path();
+
+    // VCS directories are never searched, like in the main walk
+    assert!(find(root, "name == secret.key").await.is_empty());
+    assert!(find(root, "dir AND has_descendant(name == secret.key)")
+        .await
+        .is_empty());
+    assert!(find(root, "has_child(name == .git)").await.is_empty());
+
+    // Ignored entries are skipped unless gitignore rules are turned off
+    let expr = "dir AND has_descendant(name == build.key)";
+    assert!(find_respecting_gitignore(root, expr).await.is_empty());
+    assert_eq!(
+        find(root, expr).await,
+        ["proj", "proj/target", "proj/target/debug"]
+    );
+}
+
+/// A monorepo with two packages, only one of which has a jest config
+fn setup_monorepo() -> TempDir {
+    tree([
+        f("packages/web/package.json", "{}"),
+        f("packages/web/jest.config.js", ""),
+        f("packages/web/src/app.ts", ""),
+        f("packages/web/src/components/button.ts", ""),
+        f("packages/api/package.json", "{}"),
+        f("packages/api/src/server.ts", ""),
+    ])
+}
+
+#[tokio::test]
+async fn test_ancestor() {
+    let tmp_dir = setup_monorepo();
+    let root = tmp_dir.path();
+
+    assert_eq!(
+        find(root, r#"ext == ts AND ancestor(name == "jest.config.js")"#).await,
+        [
+            "packages/web/src/app.ts",
+            "packages/web/src/components/button.ts"
+        ]
+    );
+
+    // An entry's own directory counts, so package.json's siblings are inside
+    // too, but an entry isn't its own ancestor
+    assert_eq!(
+        find(
+            root,
+            "file AND depth == 3 AND ancestor(name == package.json)"
+        )
+        .await,
+        ["packages/web/jest.config.js"]
+    );
+    assert_eq!(
+        find(root, "ext == ts AND ancestor(ext == ts)").await,
+        ["packages/web/src/components/button.ts"]
+    );
+
+    // Directories above the search root aren't considered
+    let web = root.join("packages/web/src");
+    assert_eq!(
+        find(&web, "ancestor(name == package.json)").await,
+        Vec::<String>::new()
+    );
+}
+
+#[tokio::test]
+async fn test_sibling() {
+    let tmp_dir = setup_monorepo();
+    let root = tmp_dir.path();
+
+    assert_eq!(
+        find(root, r#"sibling(name == "jest.config.js")"#).await,
+        ["packages/web/package.json", "packages/web/src"]
+    );
+
+    // An entry is not its own sibling
+    assert_eq!(
+        find(root, "name == server.ts AND sibling(ext == ts)").await,
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        find(root, "ext == ts AND sibling(ext == ts)").await,
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        find(
+            root,
+            "name == package.json AND NOT sibling(name == jest.config.js)"
+        )
+        .await,
+        ["packages/api/package.json"]
+    );
+}
+
+#[tokio::test]
+async fn test_tree_aggregates() {
+    let tmp_dir = tree([
+        f("cache/stale/a.bin", vec![0u8; 3000]),
+        f("cache/stale/nested/b.bin", vec![0u8; 500]),
+        f("cache/fresh/c.bin", vec![0u8; 10]),
+    ]);
+    let root = tmp_dir.path();
+
+    let age = |path: &str, days: u64| {
+        let mtime = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+        std::fs::File::open(root.join(path))
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+    };
+    // Children first, since creating entries touches their directory
+    for path in [
+        "cache/stale/a.bin",
+        "cache/stale/nested/b.bin",
+        "cache/stale/nested",
+        "cache/stale",
+    ] {
+        age(path, 200);
+    }
+
+    assert_eq!(
+        find(
+            root,
+            "dir AND tree_size > 1kb AND tree_newest_mtime < -180d"
+        )
+        .await,
+        ["cache/stale"]
+    );
+    assert_eq!(find(root, "tree_files == 3").await, ["cache"]);
+    assert_eq!(find(root, "tree_size == 3510").await, ["cache"]);
+    // Empty directories have no files but still have an mtime
+    create_dir_all(root.join("empty")).unwrap();
+    assert_eq!(
+        find(root, "tree_files == 0 AND tree_newest_mtime > -1d").await,
+        ["empty"]
+    );
+
+    // Aggregates never match non-directories
+    assert_eq!(
+        find(root, "file AND tree_size >= 0").await,
+        Vec::<String>::new()
+    );
+}
+
+#[tokio::test]
+async fn test_tree_aggregates_count_hard_links_once() {
+    let tmp_dir = tree([f("data/a.bin", vec![0u8; 100]), f("data/c.bin", "x")]);
+    let root = tmp_dir.path();
+    std::fs::hard_link(root.join("data/a.bin"), root.join("data/b.bin")).unwrap();
+
+    // Like `du`, whether or not --unique-inodes is given
+    assert_eq!(find(root, "tree_files == 2").await, ["data"]);
+    assert_eq!(find(root, "tree_size == 101").await, ["data"]);
+}
+
+#[tokio::test]
+async fn test_tree_aggregates_decide_directories_last() {
+    let tmp_dir = tree([f("a/b/file.txt", "x")]);
+    let root = tmp_dir.path();
+
+    let mut found = Vec::new();
+    detect::parse_and_run_fs(
+        test_logger(),
+        root,
+        false,
+        "ext == txt OR tree_files == 1".to_owned(),
+        detect::RuntimeConfig::default(),
+        |p| found.push(p.strip_prefix(root).unwrap().to_path_buf()),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        found,
+        [Path::new("a/b/file.txt"), Path::new("a/b"), Path::new("a")]
+    );
+}
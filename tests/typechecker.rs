@@ -270,6 +270,312 @@ fn test_size_value_parsing() {
         NumberMatcher::In(Bound::Left(1048577..)),
     )));
     assert_eq!(typed, expected);
+
+    let typed = parse_and_typecheck("tree_size > 1mb").unwrap();
+    let expected = Expr::Predicate(Predicate::meta(MetadataPredicate::TreeSize(
+        NumberMatcher::In(Bound::Left(1048577..)),
+    )));
+    assert_eq!(typed, expected);
+}
+
+#[test]
+fn test_mode_value_parsing() {
+    // Modes are octal, with or without a 0o prefix
+    for expr in ["mode == 644", "mode == 0644", "mode == 0o644"] {
+        let typed = parse_and_typecheck(expr).unwrap();
+        let expected = Expr::Predicate(Predicate::meta(MetadataPredicate::Mode(
+            NumberMatcher::Equals(0o644),
+        )));
+        assert_eq!(typed, expected, "Failed for {expr}");
+    }
+
+    let typed = parse_and_typecheck("mode & 0o002").unwrap();
+    let expected = Expr::Predicate(Predicate::meta(MetadataPredicate::Mode(
+        NumberMatcher::AnyBits(0o002),
+    )));
+    assert_eq!(typed, expected);
+
+    // Not octal
+    let error = parse_and_typecheck("mode == 0o789").unwrap_err();
+    assert!(matches!(error, DetectError::InvalidValue { .. }));
+
+    // Bitmask tests only apply to mode
+    let error = parse_and_typecheck("size & 4").unwrap_err();
+    assert!(matches!(error, DetectError::IncompatibleOperator { .. }));
+
+    // '&' followed by '&' is still the boolean operator
+    assert!(parse_and_typecheck("mode & 0o111 && size > 0").is_ok());
+}
+
+#[test]
+fn test_owner_value_parsing() {
+    use detect::predicate::IdMatcher;
+
+    // Names resolve to ids at typecheck time
+    let typed = parse_and_typecheck("user == root").unwrap();
+    let expected = Expr::Predicate(Predicate::meta(MetadataPredicate::User(IdMatcher::Equals(
+        0,
+    ))));
+    assert_eq!(typed, expected);
+
+    let typed = parse_and_typecheck("group in [root, 0]").unwrap();
+    let expected = Expr::Predicate(Predicate::meta(MetadataPredicate::Group(IdMatcher::In(
+        [0].into_iter().collect(),
+    ))));
+    assert_eq!(typed, expected);
+
+    let typed = parse_and_typecheck("uid > 999").unwrap();
+    let expected = Expr::Predicate(Predicate::meta(MetadataPredicate::Uid(NumberMatcher::In(
+        Bound::Left(1000..),
+    ))));
+    assert_eq!(typed, expected);
+
+    // Unknown names are reported up front rather than matching nothing
+    let error = parse_and_typecheck("user == no-such-user-for-detect").unwrap_err();
+    assert!(matches!(error, DetectError::InvalidValue { .. }));
+    let error = parse_and_typecheck("group in [root, no-such-group-for-detect]").unwrap_err();
+    assert!(matches!(error, DetectError::InvalidValue { .. }));
+
+    // Ownership names aren't ordered
+    let error = parse_and_typecheck("user > root").unwrap_err();
+    assert!(matches!(error, DetectError::IncompatibleOperator { .. }));
+}
+
+#[test]
+fn test_fstype_value_parsing() {
+    use detect::predicate::FsType;
+
+    let typed = parse_and_typecheck("fstype in [nfs4, cifs]").unwrap();
+    let expected = Expr::Predicate(Predicate::meta(MetadataPredicate::FsType(EnumMatcher::In(
+        [FsType::Nfs, FsType::Cifs].into_iter().collect(),
+    ))));
+    assert_eq!(typed, expected);
+
+    let error = parse_and_typecheck("fstype == nosuchfs").unwrap_err();
+    assert!(matches!(error, DetectError::InvalidValue { .. }));
+}
+
+#[test]
+fn test_lang_value_parsing() {
+    use detect::predicate::{HeaderPredicate, Language};
+
+    let typed = parse_and_typecheck("lang in [py, shell, Rust]").unwrap();
+    let expected = Expr::Predicate(Predicate::contents(HeaderPredicate::Lang(EnumMatcher::In(
+        [Language::Python, Language::Shell, Language::Rust]
+            .into_iter()
+            .collect(),
+    ))));
+    assert_eq!(typed, expected);
+
+    // Unknown languages are rejected with the valid names
+    let error = parse_and_typecheck("language == cobol").unwrap_err();
+    match error {
+        DetectError::InvalidValue { expected, .. } => assert!(expected.contains("python")),
+        other => panic!("expected InvalidValue, got {other:?}"),
+    }
+
+    // Languages aren't ordered
+    let error = parse_and_typecheck("lang > rust").unwrap_err();
+    assert!(matches!(error, DetectError::IncompatibleOperator { .. }));
+}
+
+#[test]
+fn test_encoding_value_parsing() {
+    use detect::predicate::{HeaderPredicate, TextEncoding};
+
+    let typed = parse_and_typecheck("encoding in [UTF-16LE, latin1]").unwrap();
+    let expected = Expr::Predicate(Predicate::contents(HeaderPredicate::Encoding(
+        EnumMatcher::In(
+            [TextEncoding::Utf16Le, TextEncoding::Latin1]
+                .into_iter()
+                .collect(),
+        ),
+    )));
+    assert_eq!(typed, expected);
+
+    let error = parse_and_typecheck("encoding == ebcdic").unwrap_err();
+    assert!(matches!(error, DetectError::InvalidValue { .. }));
+}
+
+#[test]
+fn test_eol_value_parsing() {
+    use detect::predicate::{LineCheck, LineEnding, LinesPredicate};
+
+    let typed = parse_and_typecheck("eol == DOS").unwrap();
+    let expected = Expr::Predicate(Predicate::contents(LinesPredicate::new(LineCheck::Eol(
+        EnumMatcher::Equals(LineEnding::Crlf),
+    ))));
+    assert_eq!(typed, expected);
+
+    let error = parse_and_typecheck("eol == nel").unwrap_err();
+    assert!(matches!(error, DetectError::InvalidValue { .. }));
+}
+
+#[test]
+fn test_ftype_value_parsing() {
+    let typed = parse_and_typecheck("ftype in [rust, web]").unwrap();
+    assert!(matches!(
+        typed,
+        Expr::Predicate(Predicate::Name(ref np)) if matches!(&**np, NamePredicate::FileType(_))
+    ));
+
+    // Unknown types are rejected, with the closest names
+    let error = parse_and_typecheck("ftype == rsut").unwrap_err();
+    match error {
+        DetectError::InvalidValue { expected, .. } => assert!(expected.contains("rust")),
+        other => panic!("expected InvalidValue, got {other:?}"),
+    }
+    let error = parse_and_typecheck("ftype in [rust, nosuchtype]").unwrap_err();
+    assert!(matches!(error, DetectError::InvalidValue { .. }));
+}
+
+#[test]
+fn test_xattr_selectors() {
+    use detect::predicate::XattrPredicate;
+
+    let typed = parse_and_typecheck(r#"xattr:user.origin == "ci""#).unwrap();
+    let expected = Expr::Predicate(Predicate::meta(MetadataPredicate::Xattr(
+        XattrPredicate::Value {
+            name: "user.origin".to_string(),
+            matcher: StringMatcher::Equals("ci".to_string()),
+        },
+    )));
+    assert_eq!(typed, expected);
+
+    let typed = parse_and_typecheck(r#"selinux ~= "httpd_.*_t""#).unwrap();
+    assert!(matches!(
+        typed,
+        Expr::Predicate(Predicate::Metadata(ref mp))
+            if matches!(&**mp, MetadataPredicate::Xattr(XattrPredicate::Value { name, .. }) if name == "security.selinux")
+    ));
+
+    // Attribute values are strings
+    let error = parse_and_typecheck("xattr:user.count > 5").unwrap_err();
+    assert!(matches!(error, DetectError::IncompatibleOperator { .. }));
+
+    // A name is required, and it's a plain name rather than a structured path
+    for expr in ["xattr: == ci", "xattr: contains ci"] {
+        let error = parse_and_typecheck(expr).unwrap_err();
+        assert!(matches!(error, DetectError::InvalidXattrName { .. }));
+        let help = miette::Diagnostic::help(&error).unwrap().to_string();
+        assert!(help.contains("xattr:user.origin"), "{help}");
+    }
+
+    // Only structured data formats are suggested for an unknown prefix
+    match parse_and_typecheck("xatr:user.origin == ci").unwrap_err() {
+        DetectError::UnknownStructuredFormat { suggestions, .. } => {
+            assert_eq!(suggestions.unwrap(), "Valid formats: yaml, json, toml")
+        }
+        other => panic!("expected UnknownStructuredFormat, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_tree_predicates() {
+    use detect::predicate::{TreePredicate, TreeRelation};
+
+    let typed = parse_and_typecheck("has_child(name == Cargo.toml)").unwrap();
+    let inner = parse_and_typecheck("name == Cargo.toml").unwrap();
+    let expected = Expr::Predicate(Predicate::meta(MetadataPredicate::Tree(
+        TreePredicate::new(TreeRelation::Child, inner),
+    )));
+    assert_eq!(typed, expected);
+
+    for (expr, relation) in [
+        ("has_descendant(ext == rs)", TreeRelation::Descendant(None)),
+        (
+            "has_descendant(ext == rs, depth<=3)",
+            TreeRelation::Descendant(Some(3)),
+        ),
+        (
+            "has_descendant(ext == rs, depth < 3)",
+            TreeRelation::Descendant(Some(2)),
+        ),
+        ("ancestor(name == Cargo.toml)", TreeRelation::Ancestor),
+        (r#"sibling(name == "package.json")"#, TreeRelation::Sibling),
+    ] {
+        let typed = parse_and_typecheck(expr).unwrap();
+        assert!(
+            matches!(
+                typed,
+                Expr::Predicate(Predicate::Metadata(ref mp))
+                    if matches!(&**mp, MetadataPredicate::Tree(t) if t.relation == relation)
+            ),
+            "Failed for expression: {expr}"
+        );
+    }
+
+    // The inner expression goes through the same checks as the outer one
+    let error = parse_and_typecheck("has_child(size > huge)").unwrap_err();
+    assert!(matches!(error, DetectError::InvalidValue { .. }));
+
+    let error = parse_and_typecheck("has_kid(name == foo)").unwrap_err();
+    assert!(matches!(error, DetectError::UnknownFunction { .. }));
+
+    for expr in [
+        "has_child()",
+        "has_child(name == a, name == b)",
+        r#"has_child("foo")"#,
+        "has_child(name == foo) > 1",
+        "has_descendant(name == foo, 3)",
+        "has_descendant(name == foo, depth > 3)",
+        "ancestor(name == foo, depth<=2)",
+        "sibling()",
+        "has_child(tree_size > 1mb)",
+        "sibling(NOT tree_files == 0)",
+    ] {
+        let error = parse_and_typecheck(expr).unwrap_err();
+        assert!(
+            matches!(error, DetectError::InvalidArguments { .. }),
+            "Failed for expression: {expr}: {error:?}"
+        );
+    }
+}
+
+#[test]
+fn test_hash_selectors() {
+    use detect::predicate::{Digest, HashAlgorithm, HashPredicate};
+
+    let hex = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    let typed = parse_and_typecheck(&format!("sha256 == {hex}")).unwrap();
+    let expected = Expr::Predicate(Predicate::contents(HashPredicate {
+        algorithm: HashAlgorithm::Sha256,
+        digests: vec![Digest::from_hex(hex).unwrap()],
+    }));
+    assert_eq!(typed, expected);
+
+    for expr in [
+        "sha256 == abc".to_string(),
+        format!("blake3 in [{hex}, xyz]"),
+        format!("sha256 == {hex}00"),
+    ] {
+        let error = parse_and_typecheck(&expr).unwrap_err();
+        assert!(
+            matches!(error, DetectError::InvalidValue { .. }),
+            "Failed for expression: {expr}: {error:?}"
+        );
+    }
+
+    for expr in ["sha256 ~= abc", "blake3 contains abc", "sha256 != abc"] {
+        let error = parse_and_typecheck(expr).unwrap_err();
+        assert!(
+            matches!(error, DetectError::IncompatibleOperator { .. }),
+            "Failed for expression: {expr}: {error:?}"
+        );
+    }
+
+    for expr in [
+        r#"content_eq("no/such/reference")"#,
+        "content_eq()",
+        r#"content_eq("a", "b")"#,
+        "content_eq(name == foo)",
+    ] {
+        let error = parse_and_typecheck(expr).unwrap_err();
+        assert!(
+            matches!(error, DetectError::InvalidArguments { .. }),
+            "Failed for expression: {expr}: {error:?}"
+        );
+    }
 }
 
 #[test]
@@ -289,6 +595,51 @@ fn test_temporal_value_parsing() {
     assert_eq!(typed, expected);
 }
 
+#[test]
+fn test_temporal_selector_valued_rhs() {
+    use detect::parser::typed::{TemporalOperator, TemporalSelector};
+
+    let typed = parse_and_typecheck("modified > created").unwrap();
+    let expected = Expr::Predicate(Predicate::meta(MetadataPredicate::Modified(
+        TimeMatcher::Selector(TemporalOperator::After, TemporalSelector::Created),
+    )));
+    assert_eq!(typed, expected);
+
+    // Aliases resolve on the right-hand side too
+    let typed = parse_and_typecheck("atime <= mtime").unwrap();
+    let expected = Expr::Predicate(Predicate::meta(MetadataPredicate::Accessed(
+        TimeMatcher::Selector(TemporalOperator::BeforeOrEqual, TemporalSelector::Modified),
+    )));
+    assert_eq!(typed, expected);
+
+    // Quoted values are always time literals, never selectors
+    assert!(parse_and_typecheck("modified > \"created\"").is_err());
+    // Aggregates are only known per directory, after the walk
+    assert!(parse_and_typecheck("modified > tree_newest_mtime").is_err());
+}
+
+#[test]
+fn test_temporal_reference_file_rhs() {
+    let dir = tempfile::tempdir().unwrap();
+    let stamp = dir.path().join(".stamp");
+    std::fs::write(&stamp, "").unwrap();
+    let stamp = stamp.to_str().unwrap();
+
+    let typed = parse_and_typecheck(&format!("modified > mtime_of(\"{stamp}\")")).unwrap();
+    assert!(matches!(
+        typed,
+        Expr::Predicate(Predicate::Metadata(ref mp))
+            if matches!(&**mp, MetadataPredicate::Modified(TimeMatcher::After(_)))
+    ));
+
+    let err = parse_and_typecheck("modified > mtime_of(\"no/such/stamp\")").unwrap_err();
+    assert!(matches!(err, DetectError::InvalidValue { .. }));
+
+    // Unknown reference functions fall through to time parsing
+    let err = parse_and_typecheck(&format!("modified > size_of({stamp})")).unwrap_err();
+    assert!(matches!(err, DetectError::InvalidValue { .. }));
+}
+
 #[test]
 fn test_boolean_logic_preservation() {
     // AND
@@ -511,6 +862,12 @@ fn test_content_operators() {
     // Contents does not support '!='
     let error = parse_and_typecheck("content != pattern").unwrap_err();
     assert!(matches!(error, DetectError::IncompatibleOperator { .. }));
+
+    // Neither do line patterns
+    let error = parse_and_typecheck("line in [foo, bar]").unwrap_err();
+    assert!(matches!(error, DetectError::IncompatibleOperator { .. }));
+    let error = parse_and_typecheck("all_lines != pattern").unwrap_err();
+    assert!(matches!(error, DetectError::IncompatibleOperator { .. }));
 }
 
 #[test]
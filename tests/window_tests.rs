@@ -0,0 +1,148 @@
+mod common;
+
+use common::{f, find, run, tree};
+use detect::parser::error::DetectError;
+use tempfile::TempDir;
+
+/// Sources with license headers in different places, and files long enough
+/// that their last lines are read back from the end
+fn setup() -> TempDir {
+    let mut utf16 = vec![0xff, 0xfe];
+    utf16.extend(
+        "first\r\nlast\r\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes),
+    );
+
+    tree([
+        f(
+            "licensed.rs",
+            "// Copyright 2024 Acme\n// SPDX-License-Identifier: MIT\n\nfn main() {\n}\n",
+        ),
+        f(
+            "late.rs",
+            "fn f() {}\n".repeat(30) + "// Copyright 2024 Acme\nfn main() {\n}\n",
+        ),
+        f(
+            "big.rs",
+            "// Copyright 2024 Acme\n".to_string() + &"let x = 1;\n".repeat(100_000) + "}\r\n",
+        ),
+        // The last lines span more than one block read back from the end
+        f("long.txt", "a\n".to_string() + &"y".repeat(20_000) + "\n}"),
+        f("utf16.txt", utf16),
+    ])
+}
+
+#[tokio::test]
+async fn test_head() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, "head(2) contains Copyright").await,
+        ["big.rs", "licensed.rs"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "head(40) contains Copyright").await,
+        ["big.rs", "late.rs", "licensed.rs"]
+    );
+    // Patterns match one line at a time
+    assert_eq!(find(&tmp_dir, "head(1) == first").await, ["utf16.txt"]);
+    assert_eq!(
+        find(&tmp_dir, r#"head(3) ~= "^// SPDX""#).await,
+        ["licensed.rs"]
+    );
+}
+
+#[tokio::test]
+async fn test_line_range() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, "lines[1..3] contains SPDX-License-Identifier").await,
+        ["licensed.rs"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "lines[2] contains SPDX").await,
+        ["licensed.rs"]
+    );
+    assert!(find(&tmp_dir, "lines[3..5] contains SPDX").await.is_empty());
+    assert_eq!(
+        find(&tmp_dir, "lines[31] contains Copyright").await,
+        ["late.rs"]
+    );
+}
+
+#[tokio::test]
+async fn test_tail() {
+    let tmp_dir = setup();
+
+    assert_eq!(
+        find(&tmp_dir, r#"tail(5) ~= "^}$""#).await,
+        ["big.rs", "late.rs", "licensed.rs", "long.txt"]
+    );
+    assert!(find(&tmp_dir, "tail(1) contains let").await.is_empty());
+    assert_eq!(find(&tmp_dir, "tail(2) contains let").await, ["big.rs"]);
+    assert_eq!(find(&tmp_dir, r#"tail(2) ~= "^y+$""#).await, ["long.txt"]);
+    assert!(find(&tmp_dir, "name == long.txt AND tail(2) == a")
+        .await
+        .is_empty());
+    // Whole files shorter than the window
+    assert_eq!(find(&tmp_dir, "tail(3) == a").await, ["long.txt"]);
+    assert_eq!(find(&tmp_dir, "tail(1) == last").await, ["utf16.txt"]);
+    // Combined with a pattern over the rest of the file
+    assert_eq!(
+        find(&tmp_dir, r#"tail(1) == "}" AND content contains "fn main""#).await,
+        ["late.rs", "licensed.rs"]
+    );
+}
+
+#[tokio::test]
+async fn test_window_errors() {
+    let tmp_dir = setup();
+
+    for expr in [
+        "head(2)",
+        "head() contains x",
+        "head(0) contains x",
+        "head(x) contains x",
+        "head(1, 2) contains x",
+        "tail(2) in [a, b]",
+        "tail(2) != x",
+        "tail(2) > 1",
+    ] {
+        assert!(
+            matches!(
+                run(&tmp_dir, expr).await,
+                Err(DetectError::InvalidArguments { .. })
+            ),
+            "{expr}"
+        );
+    }
+    for expr in [
+        "lines[0..2] contains x",
+        "lines[3..1] contains x",
+        "lines[a] contains x",
+    ] {
+        let error = run(&tmp_dir, expr).await.unwrap_err();
+        assert!(
+            matches!(error, DetectError::InvalidLineRange { .. }),
+            "{expr}"
+        );
+        let help = miette::Diagnostic::help(&error).unwrap().to_string();
+        assert_eq!(
+            help,
+            "Use lines[N] or lines[N..M], numbered from 1, with N <= M"
+        );
+    }
+    match run(&tmp_dir, "lines[3..1] contains x").await.unwrap_err() {
+        DetectError::InvalidLineRange { range, reason, .. } => {
+            assert_eq!(range, "3..1");
+            assert_eq!(reason, "the range is empty");
+        }
+        other => panic!("expected InvalidLineRange, got {other:?}"),
+    }
+    assert!(matches!(
+        run(&tmp_dir, "lines[1..2] != x").await,
+        Err(DetectError::IncompatibleOperator { .. })
+    ));
+}
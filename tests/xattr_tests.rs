@@ -0,0 +1,95 @@
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use common::{f, find, tree};
+use std::ffi::CString;
+use std::fs;
+use std::os::fd::AsRawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// Set an extended attribute, returning false if the filesystem doesn't support it
+fn set_xattr(path: &Path, name: &str, value: &[u8]) -> bool {
+    let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    let name = CString::new(name).unwrap();
+    let rc = unsafe {
+        libc::setxattr(
+            path.as_ptr(),
+            name.as_ptr(),
+            value.as_ptr().cast(),
+            value.len(),
+            0,
+        )
+    };
+    rc == 0
+}
+
+/// Set inode flags, returning false without CAP_LINUX_IMMUTABLE or filesystem support
+fn set_flags(path: &Path, flags: libc::c_int) -> bool {
+    let file = fs::File::open(path).unwrap();
+    let rc = unsafe { libc::ioctl(file.as_raw_fd(), libc::FS_IOC_SETFLAGS, &flags) };
+    rc == 0
+}
+
+#[tokio::test]
+async fn test_xattr_values() {
+    let tmp_dir = tree([f("from_ci.tar", ""), f("local.tar", ""), f("plain.tar", "")]);
+    let ci = tmp_dir.path().join("from_ci.tar");
+    let local = tmp_dir.path().join("local.tar");
+
+    if !set_xattr(&ci, "user.origin", b"ci") || !set_xattr(&local, "user.origin", b"laptop") {
+        return; // no user xattrs on this filesystem
+    }
+
+    assert_eq!(
+        find(&tmp_dir, r#"xattr:user.origin == "ci""#).await,
+        ["from_ci.tar"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "xattr:user.origin").await,
+        ["from_ci.tar", "local.tar"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "file && !xattr:user.origin").await,
+        ["plain.tar"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "xattr:user.origin ~= ^lap").await,
+        ["local.tar"]
+    );
+    assert_eq!(
+        find(&tmp_dir, "xattr:user.origin in [ci, laptop]").await,
+        ["from_ci.tar", "local.tar"]
+    );
+
+    // A binary value isn't text, so no comparison matches it
+    let signed = tmp_dir.path().join("signed.tar");
+    fs::write(&signed, "").unwrap();
+    assert!(set_xattr(&signed, "user.origin", b"\xff\xfe"));
+    assert!(find(&tmp_dir, "xattr:user.origin == ''").await.is_empty());
+    assert_eq!(
+        find(&tmp_dir, "xattr:user.origin").await,
+        ["from_ci.tar", "local.tar", "signed.tar"]
+    );
+}
+
+#[tokio::test]
+async fn test_inode_flags() {
+    let tmp_dir = tree([f("audit.log", ""), f("other.log", "")]);
+    let log = tmp_dir.path().join("audit.log");
+
+    const FS_APPEND_FL: libc::c_int = 0x20;
+    if !set_flags(&log, FS_APPEND_FL) {
+        return; // needs root and a filesystem with inode flags
+    }
+
+    let append_only = find(&tmp_dir, "append_only").await;
+    let immutable = find(&tmp_dir, "immutable").await;
+
+    // Clear the flag so the temp dir can be removed
+    assert!(set_flags(&log, 0));
+
+    assert_eq!(append_only, ["audit.log"]);
+    assert!(immutable.is_empty());
+}